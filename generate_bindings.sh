@@ -14,18 +14,22 @@ SDK_PATH=`xcodebuild -sdk macosx -version Path`
 FRAMEWORK_PATH="$SDK_PATH/System/Library/Frameworks/"
 
 PREFERENCES_HEADER_PATH="$FRAMEWORK_PATH/SystemConfiguration.framework/Headers/SCPreferences.h"
+PREFERENCES_PATH_HEADER_PATH="$FRAMEWORK_PATH/SystemConfiguration.framework/Headers/SCPreferencesPath.h"
 DYNAMIC_STORE_HEADER_PATH="$FRAMEWORK_PATH/SystemConfiguration.framework/Headers/SCDynamicStore.h"
 DYNAMIC_STORE_COPY_SPECIFIC_HEADER_PATH="$FRAMEWORK_PATH/SystemConfiguration.framework/Headers/SCDynamicStoreCopySpecific.h"
 NETWORK_CONFIGURATION_HEADER_PATH="$FRAMEWORK_PATH/SystemConfiguration.framework/Headers/SCNetworkConfiguration.h"
 NETWORK_REACHABILITY_HEADER_PATH="$FRAMEWORK_PATH/SystemConfiguration.framework/Headers/SCNetworkReachability.h"
 SCHEMA_DEFINITIONS_HEADER_PATH="$FRAMEWORK_PATH/SystemConfiguration.framework/Headers/SCSchemaDefinitions.h"
+SYSTEM_CONFIGURATION_HEADER_PATH="$FRAMEWORK_PATH/SystemConfiguration.framework/Headers/SystemConfiguration.h"
 
 PREFERENCES_BINDING_PATH="./system-configuration-sys/src/preferences.rs"
+PREFERENCES_PATH_BINDING_PATH="./system-configuration-sys/src/preferences_path.rs"
 DYNAMIC_STORE_BINDING_PATH="./system-configuration-sys/src/dynamic_store.rs"
 DYNAMIC_STORE_COPY_SPECIFIC_BINDING_PATH="./system-configuration-sys/src/dynamic_store_copy_specific.rs"
 NETWORK_CONFIGURATION_BINDING_PATH="./system-configuration-sys/src/network_configuration.rs"
 NETWORK_REACHABILITY_BINDING_PATH="./system-configuration-sys/src/network_reachability.rs"
 SCHEMA_DEFINITIONS_BINDING_PATH="./system-configuration-sys/src/schema_definitions.rs"
+SYSTEM_CONFIGURATION_BINDING_PATH="./system-configuration-sys/src/system_configuration.rs"
 
 BINDGEN_VERSION=`bindgen --version`
 
@@ -87,6 +91,7 @@ echo "Generating bindings for $PREFERENCES_HEADER_PATH"
 bindgen \
     "${BINDGEN_COMMON_ARGUMENTS[@]}" \
     --allowlist-function "SCPreferences.*" \
+    --allowlist-var "kSCPreferencesNotification.*" \
     --blocklist-type "(__)?CF.*" \
     --blocklist-type "Boolean" \
     --blocklist-type "dispatch_queue_[ts]" \
@@ -110,6 +115,28 @@ bindgen \
 
 cleanup_binding $PREFERENCES_BINDING_PATH
 
+echo ""
+echo ""
+echo "Generating bindings for $PREFERENCES_PATH_HEADER_PATH"
+
+bindgen \
+    "${BINDGEN_COMMON_ARGUMENTS[@]}" \
+    --allowlist-function "SCPreferencesPath.*" \
+    --blocklist-type "(__)?CF.*" \
+    --blocklist-type "Boolean" \
+    --blocklist-type "(__)?SCPreferences.*" \
+    --raw-line "use core_foundation_sys::base::Boolean;" \
+    --raw-line "use core_foundation_sys::dictionary::CFDictionaryRef;" \
+    --raw-line "use core_foundation_sys::string::CFStringRef;" \
+    --raw-line "" \
+    --raw-line "use crate::preferences::SCPreferencesRef;" \
+    -o $PREFERENCES_PATH_BINDING_PATH \
+    $PREFERENCES_PATH_HEADER_PATH -- \
+    -I$SDK_PATH/usr/include \
+    -F$FRAMEWORK_PATH
+
+cleanup_binding $PREFERENCES_PATH_BINDING_PATH
+
 echo ""
 echo ""
 echo "Generating bindings for $DYNAMIC_STORE_HEADER_PATH"
@@ -166,7 +193,9 @@ bindgen \
     "${BINDGEN_COMMON_ARGUMENTS[@]}" \
     --allowlist-function "SCNetwork.*" \
     --allowlist-function "SCBondInterface.*" \
-    --allowlist-var "kSC(NetworkInterface|BondStatus).*" \
+    --allowlist-function "SCBondStatus.*" \
+    --allowlist-function "SCVLANInterface.*" \
+    --allowlist-var "kSC(NetworkInterface|NetworkProtocolType|BondStatus).*" \
     --blocklist-type "SCNetworkReachability.*" \
     --blocklist-function "SCNetworkReachability.*" \
     --blocklist-type "dispatch_queue_[ts]" \
@@ -180,6 +209,7 @@ bindgen \
     --raw-line "use core_foundation_sys::base::{Boolean, CFIndex, CFAllocatorRef, CFTypeID};" \
     --raw-line "use core_foundation_sys::string::CFStringRef;" \
     --raw-line "use core_foundation_sys::dictionary::CFDictionaryRef;" \
+    --raw-line "use core_foundation_sys::number::CFNumberRef;" \
     --raw-line "use core_foundation_sys::runloop::CFRunLoopRef;" \
     --raw-line "" \
     --raw-line "use crate::dispatch_queue_t;" \
@@ -247,3 +277,24 @@ bindgen \
     -F$FRAMEWORK_PATH
 
 cleanup_binding $SCHEMA_DEFINITIONS_BINDING_PATH
+
+
+echo ""
+echo ""
+echo "Generating bindings for $SYSTEM_CONFIGURATION_HEADER_PATH"
+
+bindgen \
+    "${BINDGEN_COMMON_ARGUMENTS[@]}" \
+    --allowlist-function "SC(Error|ErrorString|CopyLastError)" \
+    --allowlist-var "kSCStatus.*" \
+    --allowlist-var "kCFErrorDomainSystemConfiguration" \
+    --blocklist-type "(__)?CF.*" \
+    --blocklist-type "dispatch_queue_[ts]" \
+    --raw-line "use core_foundation_sys::error::CFErrorRef;" \
+    --raw-line "use core_foundation_sys::string::CFStringRef;" \
+    -o $SYSTEM_CONFIGURATION_BINDING_PATH \
+    $SYSTEM_CONFIGURATION_HEADER_PATH -- \
+    -I$SDK_PATH/usr/include \
+    -F$FRAMEWORK_PATH
+
+cleanup_binding $SYSTEM_CONFIGURATION_BINDING_PATH
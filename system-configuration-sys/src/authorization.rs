@@ -0,0 +1,34 @@
+//! Minimal bindings to the parts of Authorization Services in the Security framework needed to
+//! create an authorized `SCPreferences` session from an authorization handed over by another
+//! process.
+
+use core::ffi::c_char;
+
+use crate::preferences::AuthorizationRef;
+
+pub type OSStatus = i32;
+pub type AuthorizationFlags = u32;
+
+pub const errAuthorizationSuccess: OSStatus = 0;
+
+pub const kAuthorizationFlagDefaults: AuthorizationFlags = 0;
+pub const kAuthorizationFlagDestroyRights: AuthorizationFlags = 1 << 3;
+
+pub const kAuthorizationExternalFormLength: usize = 32;
+
+#[repr(C)]
+pub struct AuthorizationExternalForm {
+    pub bytes: [c_char; kAuthorizationExternalFormLength],
+}
+
+extern "C" {
+    pub fn AuthorizationCreateFromExternalForm(
+        extForm: *const AuthorizationExternalForm,
+        authorization: *mut AuthorizationRef,
+    ) -> OSStatus;
+
+    pub fn AuthorizationFree(
+        authorization: AuthorizationRef,
+        flags: AuthorizationFlags,
+    ) -> OSStatus;
+}
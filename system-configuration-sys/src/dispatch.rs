@@ -0,0 +1,36 @@
+//! Minimal bindings to the parts of libdispatch (Grand Central Dispatch) needed to deliver
+//! SystemConfiguration callbacks on a dispatch queue. libdispatch is part of libSystem, so no
+//! extra library needs to be linked.
+
+use core::ffi::{c_char, c_long, c_ulong, c_void};
+
+use crate::dispatch_queue_t;
+
+pub type dispatch_object_t = *mut c_void;
+pub type dispatch_queue_attr_t = *mut c_void;
+pub type dispatch_queue_global_t = dispatch_queue_t;
+
+pub const QOS_CLASS_USER_INTERACTIVE: c_long = 0x21;
+pub const QOS_CLASS_USER_INITIATED: c_long = 0x19;
+pub const QOS_CLASS_DEFAULT: c_long = 0x15;
+pub const QOS_CLASS_UTILITY: c_long = 0x11;
+pub const QOS_CLASS_BACKGROUND: c_long = 0x09;
+
+/// Attribute for creating a serial queue with `dispatch_queue_create`.
+pub const DISPATCH_QUEUE_SERIAL: dispatch_queue_attr_t = core::ptr::null_mut();
+
+extern "C" {
+    pub fn dispatch_get_global_queue(identifier: c_long, flags: c_ulong)
+        -> dispatch_queue_global_t;
+
+    pub fn dispatch_queue_create(
+        label: *const c_char,
+        attr: dispatch_queue_attr_t,
+    ) -> dispatch_queue_t;
+
+    pub fn dispatch_set_target_queue(object: dispatch_object_t, queue: dispatch_queue_t);
+
+    pub fn dispatch_retain(object: dispatch_object_t);
+
+    pub fn dispatch_release(object: dispatch_object_t);
+}
@@ -23,9 +23,13 @@ pub use libc;
 /// This is a temporary solution.
 pub type dispatch_queue_t = *mut libc::c_void;
 
+pub mod authorization;
+pub mod dispatch;
 pub mod dynamic_store;
 pub mod dynamic_store_copy_specific;
 pub mod network_configuration;
 pub mod network_reachability;
 pub mod preferences;
+pub mod preferences_path;
 pub mod schema_definitions;
+pub mod system_configuration;
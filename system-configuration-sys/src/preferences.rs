@@ -20,6 +20,9 @@ pub type __SCPreferences = c_void;
 pub type AuthorizationRef = *const AuthorizationOpaqueRef;
 pub type SCPreferencesRef = *const __SCPreferences;
 pub type SCPreferencesNotification = u32;
+pub const kSCPreferencesNotificationCommit: _bindgen_ty_67 = 1;
+pub const kSCPreferencesNotificationApply: _bindgen_ty_67 = 2;
+pub type _bindgen_ty_67 = u32;
 #[repr(C)]
 pub struct SCPreferencesContext {
     pub version: CFIndex,
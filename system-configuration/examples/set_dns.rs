@@ -31,8 +31,10 @@ fn main() {
         CFString::from_static_string("8.8.4.4"),
     ]);
 
-    let success = store.set(primary_service_path, dns_dictionary);
-    println!("success? {}", success);
+    match store.set(primary_service_path, dns_dictionary) {
+        Ok(()) => println!("DNS servers set"),
+        Err(error) => println!("Unable to set DNS servers: {}", error),
+    }
 }
 
 fn get_primary_service_uuid(store: &SCDynamicStore) -> Option<CFString> {
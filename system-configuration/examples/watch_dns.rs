@@ -7,7 +7,7 @@ use core_foundation::{
     string::CFString,
 };
 use system_configuration::{
-    dynamic_store::{SCDynamicStore, SCDynamicStoreBuilder, SCDynamicStoreCallBackContext},
+    dynamic_store::{SCDynamicStore, SCDynamicStoreBuilder},
     sys::schema_definitions::kSCPropNetDNSServerAddresses,
 };
 
@@ -15,13 +15,22 @@ use system_configuration::{
 // is detected, it will be printed to stdout.
 
 fn main() {
-    let callback_context = SCDynamicStoreCallBackContext {
-        callout: my_callback,
-        info: Context { call_count: 0 },
-    };
+    // Any state captured by the callback closure is kept between callbacks.
+    let mut call_count = 0u64;
 
     let store = SCDynamicStoreBuilder::new("my-watch-dns-store")
-        .callback_context(callback_context)
+        .callback(move |store: &SCDynamicStore, changed_keys: Vec<String>| {
+            call_count += 1;
+            println!("Callback call count: {}", call_count);
+
+            for key in changed_keys {
+                if let Some(addresses) = get_dns(store, &key) {
+                    println!("{} changed DNS to {:?}", key, addresses);
+                } else {
+                    println!("{} removed DNS", key);
+                }
+            }
+        })
         .build()
         .expect("Unable to create DynamicStore");
 
@@ -29,11 +38,10 @@ fn main() {
     let watch_patterns =
         CFArray::from_CFTypes(&[CFString::from("(State|Setup):/Network/Service/.*/DNS")]);
 
-    if store.set_notification_keys(&watch_keys, &watch_patterns) {
-        println!("Registered for notifications");
-    } else {
-        panic!("Unable to register notifications");
-    }
+    store
+        .set_notification_keys(&watch_keys, &watch_patterns)
+        .expect("Unable to register notifications");
+    println!("Registered for notifications");
 
     let run_loop_source = store
         .create_run_loop_source()
@@ -45,28 +53,7 @@ fn main() {
     CFRunLoop::run_current();
 }
 
-/// This struct acts as a user provided context/payload to each notification callback.
-/// Here one can store any type of data or state needed in the callback function.
-#[derive(Debug)]
-struct Context {
-    call_count: u64,
-}
-
-#[allow(clippy::needless_pass_by_value)]
-fn my_callback(store: SCDynamicStore, changed_keys: CFArray<CFString>, context: &mut Context) {
-    context.call_count += 1;
-    println!("Callback call count: {}", context.call_count);
-
-    for key in changed_keys.iter() {
-        if let Some(addresses) = get_dns(&store, key.clone()) {
-            println!("{} changed DNS to {:?}", *key, addresses);
-        } else {
-            println!("{} removed DNS", *key);
-        }
-    }
-}
-
-fn get_dns(store: &SCDynamicStore, path: CFString) -> Option<Vec<String>> {
+fn get_dns(store: &SCDynamicStore, path: &str) -> Option<Vec<String>> {
     let dns_settings = store
         .get(path)
         .and_then(CFPropertyList::downcast_into::<CFDictionary>)?;
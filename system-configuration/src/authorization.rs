@@ -0,0 +1,94 @@
+//! Minimal safe wrapper around Authorization Services, used to open authorized preferences
+//! sessions with [`SCPreferences::with_authorization`].
+//!
+//! [`SCPreferences::with_authorization`]: ../preferences/struct.SCPreferences.html#method.with_authorization
+
+use crate::sys::{
+    authorization::{
+        errAuthorizationSuccess, kAuthorizationExternalFormLength, kAuthorizationFlagDefaults,
+        AuthorizationCreateFromExternalForm, AuthorizationExternalForm, AuthorizationFree,
+        OSStatus,
+    },
+    preferences::AuthorizationRef,
+};
+use std::{error::Error, fmt, ptr};
+
+/// An authorization handle, owned by this process and freed when dropped.
+///
+/// See [`AuthorizationRef`] for details.
+///
+/// [`AuthorizationRef`]: https://developer.apple.com/documentation/security/authorizationref?language=objc
+#[derive(Debug)]
+pub struct Authorization(AuthorizationRef);
+
+impl Authorization {
+    /// The length in bytes of the external form of an authorization.
+    pub const EXTERNAL_FORM_LENGTH: usize = kAuthorizationExternalFormLength;
+
+    /// Creates an authorization from its external form, as produced by
+    /// `AuthorizationMakeExternalForm` in the process that holds the authorization, such as a GUI
+    /// that asked the user to authenticate.
+    ///
+    /// See [`AuthorizationCreateFromExternalForm`] for details.
+    ///
+    /// [`AuthorizationCreateFromExternalForm`]: https://developer.apple.com/documentation/security/1396798-authorizationcreatefromexternalf?language=objc
+    pub fn from_external_form(
+        external_form: &[u8; kAuthorizationExternalFormLength],
+    ) -> Result<Self, AuthorizationError> {
+        let external_form = AuthorizationExternalForm {
+            bytes: external_form.map(|byte| byte as _),
+        };
+        let mut authorization_ref = ptr::null();
+        let status =
+            unsafe { AuthorizationCreateFromExternalForm(&external_form, &mut authorization_ref) };
+        if status != errAuthorizationSuccess || authorization_ref.is_null() {
+            return Err(AuthorizationError(status));
+        }
+        Ok(Authorization(authorization_ref))
+    }
+
+    /// Returns the underlying `AuthorizationRef`. It stays owned by this `Authorization`.
+    pub fn as_raw(&self) -> AuthorizationRef {
+        self.0
+    }
+}
+
+impl Drop for Authorization {
+    fn drop(&mut self) {
+        unsafe { AuthorizationFree(self.0, kAuthorizationFlagDefaults) };
+    }
+}
+
+/// Failure to create an [`Authorization`], with the `OSStatus` result code from Authorization
+/// Services.
+///
+/// [`Authorization`]: struct.Authorization.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AuthorizationError(OSStatus);
+
+impl AuthorizationError {
+    /// Returns the `OSStatus` result code, such as `errAuthorizationInvalidRef` (-60002).
+    pub fn code(&self) -> OSStatus {
+        self.0
+    }
+}
+
+impl fmt::Display for AuthorizationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Failed to create authorization (status {})", self.0)
+    }
+}
+
+impl Error for AuthorizationError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invalid_external_form() {
+        let error = Authorization::from_external_form(&[0; Authorization::EXTERNAL_FORM_LENGTH])
+            .unwrap_err();
+        assert_ne!(error.code(), errAuthorizationSuccess);
+    }
+}
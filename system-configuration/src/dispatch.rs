@@ -0,0 +1,110 @@
+//! Minimal safe wrapper around Grand Central Dispatch queues, used to deliver SystemConfiguration
+//! callbacks without running a `CFRunLoop`.
+
+use crate::sys::{
+    dispatch::{
+        dispatch_get_global_queue, dispatch_queue_create, dispatch_release, dispatch_retain,
+        dispatch_set_target_queue, DISPATCH_QUEUE_SERIAL, QOS_CLASS_BACKGROUND, QOS_CLASS_DEFAULT,
+        QOS_CLASS_USER_INITIATED, QOS_CLASS_USER_INTERACTIVE, QOS_CLASS_UTILITY,
+    },
+    dispatch_queue_t,
+};
+use std::ffi::CStr;
+
+/// The quality of service class of a global dispatch queue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum QualityOfService {
+    /// Work interacting with the user, such as animations.
+    UserInteractive,
+    /// Work the user is waiting on.
+    UserInitiated,
+    /// The default quality of service.
+    Default,
+    /// Long running work the user is not actively waiting on.
+    Utility,
+    /// Work the user is not aware of, such as maintenance tasks.
+    Background,
+}
+
+/// A reference counted dispatch queue.
+///
+/// See [`DispatchQueue`] for details.
+///
+/// [`DispatchQueue`]: https://developer.apple.com/documentation/dispatch/dispatchqueue
+#[derive(Debug)]
+pub struct DispatchQueue(dispatch_queue_t);
+
+// Dispatch queues are thread safe, and their reference count is managed atomically.
+unsafe impl Send for DispatchQueue {}
+unsafe impl Sync for DispatchQueue {}
+
+impl DispatchQueue {
+    /// Returns the global concurrent queue with the given quality of service.
+    ///
+    /// See [`dispatch_get_global_queue`] for details.
+    ///
+    /// [`dispatch_get_global_queue`]: https://developer.apple.com/documentation/dispatch/1452927-dispatch_get_global_queue
+    pub fn global(qos: QualityOfService) -> Self {
+        let identifier = match qos {
+            QualityOfService::UserInteractive => QOS_CLASS_USER_INTERACTIVE,
+            QualityOfService::UserInitiated => QOS_CLASS_USER_INITIATED,
+            QualityOfService::Default => QOS_CLASS_DEFAULT,
+            QualityOfService::Utility => QOS_CLASS_UTILITY,
+            QualityOfService::Background => QOS_CLASS_BACKGROUND,
+        };
+        // Global queues are never deallocated, so retaining and releasing them is a no-op. It is
+        // still allowed, so they can be handled just like created queues.
+        DispatchQueue(unsafe { dispatch_get_global_queue(identifier, 0) })
+    }
+
+    /// Creates a new serial queue, executing one block at a time in FIFO order.
+    ///
+    /// See [`dispatch_queue_create`] for details.
+    ///
+    /// [`dispatch_queue_create`]: https://developer.apple.com/documentation/dispatch/1453030-dispatch_queue_create
+    ///
+    /// # Panics
+    ///
+    /// Panics if the queue could not be created, which only happens when out of memory.
+    pub fn serial(label: &CStr) -> Self {
+        let queue = unsafe { dispatch_queue_create(label.as_ptr(), DISPATCH_QUEUE_SERIAL) };
+        assert!(!queue.is_null(), "Failed to create dispatch queue");
+        DispatchQueue(queue)
+    }
+
+    /// Creates a new serial queue that executes its blocks on `target`. Used to serialize work on
+    /// a concurrent queue.
+    ///
+    /// See [`dispatch_set_target_queue`] for details.
+    ///
+    /// [`dispatch_set_target_queue`]: https://developer.apple.com/documentation/dispatch/1452989-dispatch_set_target_queue
+    ///
+    /// # Panics
+    ///
+    /// Panics if the queue could not be created, just like [`serial`].
+    ///
+    /// [`serial`]: #method.serial
+    pub fn serial_targeting(label: &CStr, target: &DispatchQueue) -> Self {
+        let queue = Self::serial(label);
+        unsafe { dispatch_set_target_queue(queue.0, target.0) };
+        queue
+    }
+
+    /// Returns the underlying `dispatch_queue_t`, without changing its reference count.
+    pub fn as_raw(&self) -> dispatch_queue_t {
+        self.0
+    }
+}
+
+impl Clone for DispatchQueue {
+    fn clone(&self) -> Self {
+        unsafe { dispatch_retain(self.0) };
+        DispatchQueue(self.0)
+    }
+}
+
+impl Drop for DispatchQueue {
+    fn drop(&mut self) {
+        unsafe { dispatch_release(self.0) };
+    }
+}
@@ -12,15 +12,39 @@
 //!
 //! [`SCDynamicStore`]: https://developer.apple.com/documentation/systemconfiguration/scdynamicstore?language=objc
 
+mod key;
+mod pattern;
+mod specific;
+#[cfg(feature = "stream")]
+mod stream;
+
+pub use self::key::{
+    DynamicStoreDomain, DynamicStoreEntity, DynamicStoreKey, DynamicStoreScope, ParseKeyError,
+    ParseKeyErrorKind,
+};
+pub use self::pattern::{
+    escape_pattern, DynamicStorePattern, DynamicStorePatternBuilder, PatternError,
+    PatternErrorKind, PatternScope,
+};
+pub use self::specific::{
+    computer_name, console_user, local_host_name, location, ComputerName, ConsoleUser,
+};
+#[cfg(feature = "stream")]
+pub use self::stream::{DynamicStoreChange, DynamicStoreStream};
+
 use crate::sys::{
     dynamic_store::{
-        kSCDynamicStoreUseSessionKeys, SCDynamicStoreCallBack, SCDynamicStoreContext,
-        SCDynamicStoreCopyKeyList, SCDynamicStoreCopyValue, SCDynamicStoreCreateRunLoopSource,
-        SCDynamicStoreCreateWithOptions, SCDynamicStoreGetTypeID, SCDynamicStoreRef,
-        SCDynamicStoreRemoveValue, SCDynamicStoreSetNotificationKeys, SCDynamicStoreSetValue,
+        kSCDynamicStoreUseSessionKeys, SCDynamicStoreAddTemporaryValue, SCDynamicStoreAddValue,
+        SCDynamicStoreCallBack, SCDynamicStoreContext, SCDynamicStoreCopyKeyList,
+        SCDynamicStoreCopyMultiple, SCDynamicStoreCopyNotifiedKeys, SCDynamicStoreCopyValue,
+        SCDynamicStoreCreateRunLoopSource, SCDynamicStoreCreateWithOptions,
+        SCDynamicStoreGetTypeID, SCDynamicStoreNotifyValue, SCDynamicStoreRef,
+        SCDynamicStoreRemoveValue, SCDynamicStoreSetDispatchQueue, SCDynamicStoreSetMultiple,
+        SCDynamicStoreSetNotificationKeys, SCDynamicStoreSetValue,
     },
     dynamic_store_copy_specific::SCDynamicStoreCopyProxies,
 };
+use crate::{dispatch::DispatchQueue, error::SCError};
 use core_foundation::{
     array::{CFArray, CFArrayRef},
     base::{kCFAllocatorDefault, CFType, TCFType},
@@ -28,9 +52,17 @@ use core_foundation::{
     dictionary::CFDictionary,
     propertylist::{CFPropertyList, CFPropertyListSubClass},
     runloop::CFRunLoopSource,
-    string::CFString,
+    string::{CFString, CFStringRef},
 };
-use std::{ffi::c_void, ptr};
+use std::{
+    collections::HashMap,
+    ffi::{c_void, CStr},
+    ptr, thread,
+    time::{Duration, Instant},
+};
+
+/// How often `SCDynamicStore::wait_for_notified_keys` checks the store for changed keys.
+const NOTIFIED_KEYS_POLL_INTERVAL: Duration = Duration::from_millis(50);
 
 /// Struct describing the callback happening when a watched value in the dynamic store is changed.
 pub struct SCDynamicStoreCallBackContext<T> {
@@ -101,8 +133,27 @@ impl<T> SCDynamicStoreBuilder<T> {
         }
     }
 
+    /// Set a closure to call when a watched value in the dynamic store is changed. The closure
+    /// gets the store and the changed keys. Any state it captures is kept between calls.
+    ///
+    /// This is a shorthand for [`callback_context`] that does not need a separate callback
+    /// function and state struct.
+    ///
+    /// Defaults to having callbacks disabled.
+    ///
+    /// [`callback_context`]: #method.callback_context
+    pub fn callback<F>(self, callback: F) -> SCDynamicStoreBuilder<F>
+    where
+        F: FnMut(&SCDynamicStore, Vec<String>) + Send + 'static,
+    {
+        self.callback_context(SCDynamicStoreCallBackContext {
+            callout: call_closure::<F>,
+            info: callback,
+        })
+    }
+
     /// Create the dynamic store session.
-    pub fn build(mut self) -> Option<SCDynamicStore> {
+    pub fn build(mut self) -> Result<SCDynamicStore, SCError> {
         let store_options = self.create_store_options();
         if let Some(callback_context) = self.callback_context.take() {
             SCDynamicStore::create(
@@ -142,6 +193,39 @@ impl<T> SCDynamicStoreBuilder<T> {
     }
 }
 
+impl<T: Send> SCDynamicStoreBuilder<T> {
+    /// Create the dynamic store session, with callbacks delivered on the given dispatch queue
+    /// instead of a run loop.
+    ///
+    /// Notifications are delivered until the returned [`DispatchQueueHandle`] is dropped. The
+    /// callback runs on the threads of the dispatch queue, which is why the callback context must
+    /// be `Send`. Callbacks go through a private serial queue targeting `queue`, so they never run
+    /// concurrently, even on a concurrent queue.
+    ///
+    /// See [`SCDynamicStoreSetDispatchQueue`] for details.
+    ///
+    /// [`DispatchQueueHandle`]: struct.DispatchQueueHandle.html
+    /// [`SCDynamicStoreSetDispatchQueue`]: https://developer.apple.com/documentation/systemconfiguration/1437824-scdynamicstoresetdispatchqueue?language=objc
+    pub fn build_with_dispatch_queue(
+        self,
+        queue: &DispatchQueue,
+    ) -> Result<(SCDynamicStore, DispatchQueueHandle), SCError> {
+        let store = self.build()?;
+        let serial_queue = DispatchQueue::serial_targeting(
+            CStr::from_bytes_with_nul(b"system-configuration.SCDynamicStore\0").unwrap(),
+            queue,
+        );
+        SCError::check(unsafe {
+            SCDynamicStoreSetDispatchQueue(store.as_concrete_TypeRef(), serial_queue.as_raw())
+        })?;
+        let handle = DispatchQueueHandle {
+            store: store.clone(),
+            _queue: serial_queue,
+        };
+        Ok((store, handle))
+    }
+}
+
 declare_TCFType! {
     /// Access to the key-value pairs in the dynamic store of a running system.
     ///
@@ -161,7 +245,7 @@ impl SCDynamicStore {
         store_options: &CFDictionary,
         callout: SCDynamicStoreCallBack,
         context: *mut SCDynamicStoreContext,
-    ) -> Option<Self> {
+    ) -> Result<Self, SCError> {
         unsafe {
             let store = SCDynamicStoreCreateWithOptions(
                 kCFAllocatorDefault,
@@ -171,18 +255,18 @@ impl SCDynamicStore {
                 context,
             );
             if store.is_null() {
-                None
+                Err(SCError::last())
             } else {
-                Some(SCDynamicStore::wrap_under_create_rule(store))
+                Ok(SCDynamicStore::wrap_under_create_rule(store))
             }
         }
     }
 
     /// Returns the keys that represent the current dynamic store entries that match the specified
-    /// pattern. Or `None` if an error occurred.
+    /// pattern.
     ///
     /// `pattern` - A regular expression pattern used to match the dynamic store keys.
-    pub fn get_keys<S: Into<CFString>>(&self, pattern: S) -> Option<CFArray<CFString>> {
+    pub fn get_keys<S: Into<CFString>>(&self, pattern: S) -> Result<CFArray<CFString>, SCError> {
         let cf_pattern = pattern.into();
         unsafe {
             let array_ref = SCDynamicStoreCopyKeyList(
@@ -190,22 +274,21 @@ impl SCDynamicStore {
                 cf_pattern.as_concrete_TypeRef(),
             );
             if array_ref.is_null() {
-                None
+                Err(SCError::last())
             } else {
-                Some(CFArray::wrap_under_create_rule(array_ref))
+                Ok(CFArray::wrap_under_create_rule(array_ref))
             }
         }
     }
 
-    /// Returns the key-value pairs that represent the current internet proxy settings. Or `None` if
-    /// no proxy settings have been defined or if an error occurred.
-    pub fn get_proxies(&self) -> Option<CFDictionary<CFString, CFType>> {
+    /// Returns the key-value pairs that represent the current internet proxy settings.
+    pub fn get_proxies(&self) -> Result<CFDictionary<CFString, CFType>, SCError> {
         unsafe {
             let dictionary_ref = SCDynamicStoreCopyProxies(self.as_concrete_TypeRef());
-            if !dictionary_ref.is_null() {
-                Some(CFDictionary::wrap_under_create_rule(dictionary_ref))
+            if dictionary_ref.is_null() {
+                Err(SCError::last())
             } else {
-                None
+                Ok(CFDictionary::wrap_under_create_rule(dictionary_ref))
             }
         }
     }
@@ -226,33 +309,192 @@ impl SCDynamicStore {
         }
     }
 
+    /// Returns the values of all the given `keys`, and of all keys matching any of the given
+    /// `patterns`, read atomically from the store. Keys that do not exist are left out of the
+    /// returned map.
+    ///
+    /// See [`SCDynamicStoreCopyMultiple`] for details.
+    ///
+    /// [`SCDynamicStoreCopyMultiple`]: https://developer.apple.com/documentation/systemconfiguration/1437817-scdynamicstorecopymultiple?language=objc
+    pub fn get_multiple<T1, T2>(
+        &self,
+        keys: &CFArray<T1>,
+        patterns: &CFArray<T2>,
+    ) -> Result<HashMap<String, CFPropertyList>, SCError> {
+        let dictionary = unsafe {
+            let dictionary_ref = SCDynamicStoreCopyMultiple(
+                self.as_concrete_TypeRef(),
+                keys.as_concrete_TypeRef(),
+                patterns.as_concrete_TypeRef(),
+            );
+            if dictionary_ref.is_null() {
+                return Err(SCError::last());
+            }
+            CFDictionary::<CFString, CFType>::wrap_under_create_rule(dictionary_ref)
+        };
+
+        let (key_refs, value_refs) = dictionary.get_keys_and_values();
+        let values = key_refs
+            .into_iter()
+            .zip(value_refs)
+            .map(|(key_ref, value_ref)| unsafe {
+                (
+                    CFString::wrap_under_get_rule(key_ref as CFStringRef).to_string(),
+                    CFPropertyList::wrap_under_get_rule(value_ref),
+                )
+            })
+            .collect();
+        Ok(values)
+    }
+
     /// Sets the value of the given key. Overwrites existing values.
-    /// Returns `true` on success, false on failure.
-    pub fn set<S: Into<CFString>, V: CFPropertyListSubClass>(&self, key: S, value: V) -> bool {
+    pub fn set<S: Into<CFString>, V: CFPropertyListSubClass>(
+        &self,
+        key: S,
+        value: V,
+    ) -> Result<(), SCError> {
         self.set_raw(key, &value.into_CFPropertyList())
     }
 
     /// Sets the value of the given key. Overwrites existing values.
-    /// Returns `true` on success, false on failure.
-    pub fn set_raw<S: Into<CFString>>(&self, key: S, value: &CFPropertyList) -> bool {
+    pub fn set_raw<S: Into<CFString>>(
+        &self,
+        key: S,
+        value: &CFPropertyList,
+    ) -> Result<(), SCError> {
         let cf_key = key.into();
-        let success = unsafe {
+        SCError::check(unsafe {
             SCDynamicStoreSetValue(
                 self.as_concrete_TypeRef(),
                 cf_key.as_concrete_TypeRef(),
                 value.as_concrete_TypeRef(),
             )
-        };
-        success != 0
+        })
+    }
+
+    /// Adds the given key-value pair, but only if the key does not already exist in the store.
+    /// Never overwrites the value of another process. Fails with [`SCError::KeyExists`] if the key
+    /// exists.
+    ///
+    /// See [`SCDynamicStoreAddValue`] for details.
+    ///
+    /// [`SCError::KeyExists`]: ../error/enum.SCError.html#variant.KeyExists
+    /// [`SCDynamicStoreAddValue`]: https://developer.apple.com/documentation/systemconfiguration/1437820-scdynamicstoreaddvalue?language=objc
+    pub fn add<S: Into<CFString>, V: CFPropertyListSubClass>(
+        &self,
+        key: S,
+        value: V,
+    ) -> Result<(), SCError> {
+        self.add_raw(key, &value.into_CFPropertyList())
+    }
+
+    /// Adds the given key-value pair, but only if the key does not already exist in the store.
+    /// Never overwrites the value of another process. Fails with [`SCError::KeyExists`] if the key
+    /// exists.
+    ///
+    /// [`SCError::KeyExists`]: ../error/enum.SCError.html#variant.KeyExists
+    pub fn add_raw<S: Into<CFString>>(
+        &self,
+        key: S,
+        value: &CFPropertyList,
+    ) -> Result<(), SCError> {
+        let cf_key = key.into();
+        SCError::check(unsafe {
+            SCDynamicStoreAddValue(
+                self.as_concrete_TypeRef(),
+                cf_key.as_concrete_TypeRef(),
+                value.as_concrete_TypeRef(),
+            )
+        })
+    }
+
+    /// Adds the given key-value pair, but only if the key does not already exist in the store.
+    /// The value is removed by the System Configuration server when this session ends, for
+    /// example when the process exits or crashes. Fails with [`SCError::KeyExists`] if the key
+    /// exists.
+    ///
+    /// See [`SCDynamicStoreAddTemporaryValue`] for details.
+    ///
+    /// [`SCError::KeyExists`]: ../error/enum.SCError.html#variant.KeyExists
+    /// [`SCDynamicStoreAddTemporaryValue`]: https://developer.apple.com/documentation/systemconfiguration/1437826-scdynamicstoreaddtemporaryvalue?language=objc
+    pub fn add_temporary<S: Into<CFString>, V: CFPropertyListSubClass>(
+        &self,
+        key: S,
+        value: V,
+    ) -> Result<(), SCError> {
+        self.add_temporary_raw(key, &value.into_CFPropertyList())
+    }
+
+    /// Adds the given key-value pair, but only if the key does not already exist in the store.
+    /// The value is removed by the System Configuration server when this session ends. Fails with
+    /// [`SCError::KeyExists`] if the key exists.
+    ///
+    /// [`SCError::KeyExists`]: ../error/enum.SCError.html#variant.KeyExists
+    pub fn add_temporary_raw<S: Into<CFString>>(
+        &self,
+        key: S,
+        value: &CFPropertyList,
+    ) -> Result<(), SCError> {
+        let cf_key = key.into();
+        SCError::check(unsafe {
+            SCDynamicStoreAddTemporaryValue(
+                self.as_concrete_TypeRef(),
+                cf_key.as_concrete_TypeRef(),
+                value.as_concrete_TypeRef(),
+            )
+        })
+    }
+
+    /// Sets the values in `to_set`, removes the keys in `to_remove` and sends notifications for the
+    /// keys in `to_notify`, all in a single atomic operation. Observers never see some of the
+    /// changes without the others.
+    ///
+    /// See [`SCDynamicStoreSetMultiple`] for details.
+    ///
+    /// [`SCDynamicStoreSetMultiple`]: https://developer.apple.com/documentation/systemconfiguration/1437823-scdynamicstoresetmultiple?language=objc
+    pub fn set_multiple<T1, T2>(
+        &self,
+        to_set: &HashMap<String, CFPropertyList>,
+        to_remove: &CFArray<T1>,
+        to_notify: &CFArray<T2>,
+    ) -> Result<(), SCError> {
+        let pairs = to_set
+            .iter()
+            .map(|(key, value)| (CFString::new(key), value.as_CFType()))
+            .collect::<Vec<_>>();
+        let to_set = CFDictionary::from_CFType_pairs(&pairs);
+        SCError::check(unsafe {
+            SCDynamicStoreSetMultiple(
+                self.as_concrete_TypeRef(),
+                to_set.as_concrete_TypeRef(),
+                to_remove.as_concrete_TypeRef(),
+                to_notify.as_concrete_TypeRef(),
+            )
+        })
     }
 
     /// Removes the value of the specified key from the dynamic store.
-    pub fn remove<S: Into<CFString>>(&self, key: S) -> bool {
+    pub fn remove<S: Into<CFString>>(&self, key: S) -> Result<(), SCError> {
         let cf_key = key.into();
-        let success = unsafe {
+        SCError::check(unsafe {
             SCDynamicStoreRemoveValue(self.as_concrete_TypeRef(), cf_key.as_concrete_TypeRef())
-        };
-        success != 0
+        })
+    }
+
+    /// Triggers a notification for the given key to all observers, without changing its value.
+    ///
+    /// To notify as part of a batch update, pass the key in the `to_notify` argument of
+    /// [`set_multiple`] instead.
+    ///
+    /// See [`SCDynamicStoreNotifyValue`] for details.
+    ///
+    /// [`set_multiple`]: #method.set_multiple
+    /// [`SCDynamicStoreNotifyValue`]: https://developer.apple.com/documentation/systemconfiguration/1437825-scdynamicstorenotifyvalue?language=objc
+    pub fn notify<S: Into<CFString>>(&self, key: S) -> Result<(), SCError> {
+        let cf_key = key.into();
+        SCError::check(unsafe {
+            SCDynamicStoreNotifyValue(self.as_concrete_TypeRef(), cf_key.as_concrete_TypeRef())
+        })
     }
 
     /// Specifies a set of keys and key patterns that should be monitored for changes.
@@ -260,19 +502,77 @@ impl SCDynamicStore {
         &self,
         keys: &CFArray<T1>,
         patterns: &CFArray<T2>,
-    ) -> bool {
-        let success = unsafe {
+    ) -> Result<(), SCError> {
+        SCError::check(unsafe {
             SCDynamicStoreSetNotificationKeys(
                 self.as_concrete_TypeRef(),
                 keys.as_concrete_TypeRef(),
                 patterns.as_concrete_TypeRef(),
             )
-        };
-        success != 0
+        })
+    }
+
+    /// Specifies a set of typed keys and validated patterns that should be monitored for changes.
+    /// Replaces any keys and patterns set earlier, just like [`set_notification_keys`].
+    ///
+    /// [`set_notification_keys`]: #method.set_notification_keys
+    pub fn set_notifications(
+        &self,
+        keys: &[DynamicStoreKey],
+        patterns: &[DynamicStorePattern],
+    ) -> Result<(), SCError> {
+        let keys = keys.iter().map(CFString::from).collect::<Vec<_>>();
+        let patterns = patterns.iter().map(CFString::from).collect::<Vec<_>>();
+        self.set_notification_keys(
+            &CFArray::from_CFTypes(&keys),
+            &CFArray::from_CFTypes(&patterns),
+        )
+    }
+
+    /// Returns the keys that changed since the notification keys were set, or since the last
+    /// call to this method. Returns an empty list if nothing has changed.
+    ///
+    /// This allows polling for changes without a run loop or dispatch queue. Changes are only
+    /// recorded for a store created without a callback, since a callback consumes the changed
+    /// keys as they are delivered.
+    ///
+    /// See [`SCDynamicStoreCopyNotifiedKeys`] for details.
+    ///
+    /// [`SCDynamicStoreCopyNotifiedKeys`]: https://developer.apple.com/documentation/systemconfiguration/1437832-scdynamicstorecopynotifiedkeys?language=objc
+    pub fn notified_keys(&self) -> Result<Vec<String>, SCError> {
+        unsafe {
+            let array_ref = SCDynamicStoreCopyNotifiedKeys(self.as_concrete_TypeRef());
+            if array_ref.is_null() {
+                Err(SCError::last())
+            } else {
+                let keys = CFArray::<CFString>::wrap_under_create_rule(array_ref);
+                Ok(keys.iter().map(|key| key.to_string()).collect())
+            }
+        }
+    }
+
+    /// Blocks until any of the monitored keys change, or until `timeout` has passed, and returns
+    /// the changed keys like [`notified_keys`]. Returns an empty list if the timeout passed without
+    /// any changes.
+    ///
+    /// The store is polled while waiting, so changes are noticed up to 50 milliseconds after they
+    /// happen. Use a callback instead to be notified right away.
+    ///
+    /// [`notified_keys`]: #method.notified_keys
+    pub fn wait_for_notified_keys(&self, timeout: Duration) -> Result<Vec<String>, SCError> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let keys = self.notified_keys()?;
+            let now = Instant::now();
+            if !keys.is_empty() || now >= deadline {
+                return Ok(keys);
+            }
+            thread::sleep(NOTIFIED_KEYS_POLL_INTERVAL.min(deadline - now));
+        }
     }
 
     /// Creates a run loop source object that can be added to the application's run loop.
-    pub fn create_run_loop_source(&self) -> Option<CFRunLoopSource> {
+    pub fn create_run_loop_source(&self) -> Result<CFRunLoopSource, SCError> {
         unsafe {
             let run_loop_source_ref = SCDynamicStoreCreateRunLoopSource(
                 kCFAllocatorDefault,
@@ -280,14 +580,34 @@ impl SCDynamicStore {
                 0,
             );
             if run_loop_source_ref.is_null() {
-                None
+                Err(SCError::last())
             } else {
-                Some(CFRunLoopSource::wrap_under_create_rule(run_loop_source_ref))
+                Ok(CFRunLoopSource::wrap_under_create_rule(run_loop_source_ref))
             }
         }
     }
 }
 
+/// Keeps delivering the callbacks of an [`SCDynamicStore`] on a dispatch queue. The queue is
+/// detached from the store when this is dropped.
+///
+/// Created by [`SCDynamicStoreBuilder::build_with_dispatch_queue`].
+///
+/// [`SCDynamicStore`]: struct.SCDynamicStore.html
+/// [`SCDynamicStoreBuilder::build_with_dispatch_queue`]: struct.SCDynamicStoreBuilder.html#method.build_with_dispatch_queue
+pub struct DispatchQueueHandle {
+    store: SCDynamicStore,
+    _queue: DispatchQueue,
+}
+
+impl Drop for DispatchQueueHandle {
+    fn drop(&mut self) {
+        unsafe {
+            SCDynamicStoreSetDispatchQueue(self.store.as_concrete_TypeRef(), ptr::null_mut())
+        };
+    }
+}
+
 /// The raw callback used by the safe `SCDynamicStore` to convert from the `SCDynamicStoreCallBack`
 /// to the `SCDynamicStoreCallBackT`
 unsafe extern "C" fn convert_callback<T>(
@@ -302,8 +622,187 @@ unsafe extern "C" fn convert_callback<T>(
     (context.callout)(store, changed_keys, &mut context.info);
 }
 
+/// The callout used by [`SCDynamicStoreBuilder::callback`] to call the closure stored as the
+/// callback info.
+///
+/// [`SCDynamicStoreBuilder::callback`]: struct.SCDynamicStoreBuilder.html#method.callback
+fn call_closure<F>(store: SCDynamicStore, changed_keys: CFArray<CFString>, callback: &mut F)
+where
+    F: FnMut(&SCDynamicStore, Vec<String>),
+{
+    let changed_keys = changed_keys.iter().map(|key| key.to_string()).collect();
+    callback(&store, changed_keys);
+}
+
 // Release function called by core foundation on release of the dynamic store context.
 unsafe extern "C" fn release_callback_context<T>(context_ptr: *const c_void) {
     // Bring back the context object from raw ptr so it is correctly freed.
     let _context = Box::from_raw(context_ptr as *mut SCDynamicStoreCallBackContext<T>);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dispatch::QualityOfService;
+    use std::sync::mpsc;
+
+    #[test]
+    fn get_multiple() {
+        let store = SCDynamicStoreBuilder::new("test").build().unwrap();
+        let pattern = DynamicStorePattern::builder(PatternScope::Global)
+            .domain(DynamicStoreDomain::State)
+            .any_entity()
+            .build();
+        let keys = store.get_keys(&pattern).unwrap();
+
+        let values = store
+            .get_multiple(
+                &CFArray::<CFString>::from_CFTypes(&[]),
+                &CFArray::from_CFTypes(&[CFString::from(&pattern)]),
+            )
+            .unwrap();
+        assert!(keys.iter().all(|key| values.contains_key(&key.to_string())));
+    }
+
+    #[test]
+    fn add_existing_key() {
+        let store = SCDynamicStoreBuilder::new("test").build().unwrap();
+        let key = DynamicStoreKey::global(
+            DynamicStoreDomain::State,
+            DynamicStoreEntity::from("system-configuration-rs-add-test"),
+        );
+        let value = CFString::new("second").into_CFPropertyList();
+
+        store.add(&key, CFString::new("first")).unwrap();
+        assert_eq!(
+            store.add(&key, CFString::new("second")),
+            Err(SCError::KeyExists)
+        );
+        assert_eq!(store.add_raw(&key, &value), Err(SCError::KeyExists));
+        assert_eq!(
+            store.add_temporary(&key, CFString::new("second")),
+            Err(SCError::KeyExists)
+        );
+        let stored = store
+            .get(&key)
+            .and_then(CFPropertyList::downcast_into::<CFString>);
+        assert_eq!(
+            stored.map(|value| value.to_string()),
+            Some("first".to_owned())
+        );
+        store.remove(&key).unwrap();
+
+        store.add_temporary_raw(&key, &value).unwrap();
+        assert_eq!(
+            store.add_temporary_raw(&key, &value),
+            Err(SCError::KeyExists)
+        );
+        assert_eq!(store.add_raw(&key, &value), Err(SCError::KeyExists));
+        store.remove(&key).unwrap();
+    }
+
+    #[test]
+    fn wait_for_notified_keys() {
+        let store = SCDynamicStoreBuilder::new("test").build().unwrap();
+        let key = DynamicStoreKey::global(
+            DynamicStoreDomain::State,
+            DynamicStoreEntity::from("system-configuration-rs-notified-keys-test"),
+        );
+        store
+            .set_notifications(std::slice::from_ref(&key), &[])
+            .unwrap();
+        assert_eq!(store.notified_keys(), Ok(vec![]));
+        assert_eq!(
+            store.wait_for_notified_keys(Duration::from_millis(10)),
+            Ok(vec![])
+        );
+
+        store.notify(&key).unwrap();
+        assert_eq!(
+            store.wait_for_notified_keys(Duration::from_secs(5)),
+            Ok(vec![key.to_string()])
+        );
+        assert_eq!(store.notified_keys(), Ok(vec![]));
+    }
+
+    #[test]
+    fn closure_callback() {
+        let mut changes = Vec::new();
+        let store = SCDynamicStoreBuilder::new("test")
+            .callback(move |_: &SCDynamicStore, changed_keys: Vec<String>| {
+                changes.extend(changed_keys)
+            })
+            .build()
+            .unwrap();
+        assert!(store.create_run_loop_source().is_ok());
+    }
+
+    #[test]
+    fn closure_callback_receives_changed_keys() {
+        let key = DynamicStoreKey::global(
+            DynamicStoreDomain::State,
+            DynamicStoreEntity::from("system-configuration-rs-closure-test"),
+        );
+        let (sender, receiver) = mpsc::channel();
+        let mut calls = 0;
+        let queue = DispatchQueue::global(QualityOfService::Default);
+        let (store, _handle) = SCDynamicStoreBuilder::new("test")
+            .callback(move |_: &SCDynamicStore, changed_keys: Vec<String>| {
+                calls += 1;
+                let _ = sender.send((calls, changed_keys));
+            })
+            .build_with_dispatch_queue(&queue)
+            .unwrap();
+        store
+            .set_notifications(std::slice::from_ref(&key), &[])
+            .unwrap();
+
+        store.notify(&key).unwrap();
+        assert_eq!(
+            receiver.recv_timeout(Duration::from_secs(5)),
+            Ok((1, vec![key.to_string()]))
+        );
+        store.notify(&key).unwrap();
+        assert_eq!(
+            receiver.recv_timeout(Duration::from_secs(5)),
+            Ok((2, vec![key.to_string()]))
+        );
+    }
+
+    #[test]
+    fn dispatch_queue() {
+        let key = DynamicStoreKey::global(
+            DynamicStoreDomain::State,
+            DynamicStoreEntity::from("system-configuration-rs-dispatch-test"),
+        );
+        let (sender, receiver) = mpsc::channel();
+        let callback_context = SCDynamicStoreCallBackContext {
+            callout: |_,
+                      changed_keys: CFArray<CFString>,
+                      sender: &mut mpsc::Sender<Vec<String>>| {
+                let changed_keys = changed_keys.iter().map(|key| key.to_string()).collect();
+                let _ = sender.send(changed_keys);
+            },
+            info: sender,
+        };
+        let queue = DispatchQueue::global(QualityOfService::Default);
+        let (store, handle) = SCDynamicStoreBuilder::new("test")
+            .callback_context(callback_context)
+            .build_with_dispatch_queue(&queue)
+            .unwrap();
+        store
+            .set_notifications(std::slice::from_ref(&key), &[])
+            .unwrap();
+
+        store.notify(&key).unwrap();
+        assert_eq!(
+            receiver.recv_timeout(Duration::from_secs(5)),
+            Ok(vec![key.to_string()])
+        );
+
+        // No callbacks are delivered once the queue is detached.
+        drop(handle);
+        store.notify(&key).unwrap();
+        assert!(receiver.recv_timeout(Duration::from_millis(100)).is_err());
+    }
+}
@@ -0,0 +1,565 @@
+//! Typed representation of the network keys in the dynamic store.
+//!
+//! Keys have the form `<domain>:/Network/<scope>[/<id>][/<entity>]`, for example
+//! `State:/Network/Service/<service id>/DNS` or `Setup:/Network/Global/IPv4`. See
+//! [`SCDynamicStoreKey`] for the functions Apple provides to build the same keys.
+//!
+//! [`SCDynamicStoreKey`]: https://developer.apple.com/documentation/systemconfiguration/scdynamicstorekey?language=objc
+
+use core_foundation::string::CFString;
+use std::{
+    convert::TryFrom,
+    error::Error,
+    fmt::{self, Display},
+    str::FromStr,
+};
+
+/// The separator between the components of a dynamic store key.
+const SEPARATOR: char = '/';
+
+/// The component following the domain in all network keys. Same as `kSCCompNetwork`.
+const NETWORK_COMPONENT: &str = "Network";
+
+/// The domain, or top level namespace, a dynamic store key lives in.
+///
+/// See [_Dynamic Store Domains_] for details.
+///
+/// [_Dynamic Store Domains_]: https://developer.apple.com/documentation/systemconfiguration/scschemadefinitions?language=objc
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DynamicStoreDomain {
+    /// Configuration as read from the persistent preferences. Same as
+    /// `kSCDynamicStoreDomainSetup`.
+    Setup,
+    /// The currently active state of the system. Same as `kSCDynamicStoreDomainState`.
+    State,
+    /// Values read from files. Same as `kSCDynamicStoreDomainFile`.
+    File,
+    /// Values owned by configd plugins. Same as `kSCDynamicStoreDomainPlugin`.
+    Plugin,
+    /// Values mirrored from the preferences. Same as `kSCDynamicStoreDomainPrefs`.
+    Prefs,
+}
+
+impl DynamicStoreDomain {
+    /// Returns the key prefix of this domain, including the trailing colon.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DynamicStoreDomain::Setup => "Setup:",
+            DynamicStoreDomain::State => "State:",
+            DynamicStoreDomain::File => "File:",
+            DynamicStoreDomain::Plugin => "Plugin:",
+            DynamicStoreDomain::Prefs => "Prefs:",
+        }
+    }
+
+    /// Tries to match `domain` against the domain prefixes, including the trailing colon.
+    /// Returns `None` if no domain matches.
+    pub fn from_str_prefix(domain: &str) -> Option<Self> {
+        [
+            DynamicStoreDomain::Setup,
+            DynamicStoreDomain::State,
+            DynamicStoreDomain::File,
+            DynamicStoreDomain::Plugin,
+            DynamicStoreDomain::Prefs,
+        ]
+        .iter()
+        .copied()
+        .find(|candidate| candidate.as_str() == domain)
+    }
+}
+
+impl Display for DynamicStoreDomain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The part of the network configuration a key describes.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum DynamicStoreScope {
+    /// System wide settings, such as the primary service. Same as `kSCCompGlobal`.
+    Global,
+    /// Settings for the network service with the given service ID. Same as `kSCCompService`.
+    Service(String),
+    /// Settings for the interface with the given _BSD_ name, such as `en0`. Same as
+    /// `kSCCompInterface`.
+    Interface(String),
+}
+
+impl DynamicStoreScope {
+    /// Returns the name of the scope component, such as `Service`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DynamicStoreScope::Global => "Global",
+            DynamicStoreScope::Service(_) => "Service",
+            DynamicStoreScope::Interface(_) => "Interface",
+        }
+    }
+
+    /// Returns the service ID or interface name of this scope. `None` for the global scope.
+    pub fn id(&self) -> Option<&str> {
+        match self {
+            DynamicStoreScope::Global => None,
+            DynamicStoreScope::Service(id) | DynamicStoreScope::Interface(id) => Some(id),
+        }
+    }
+}
+
+/// The network entities a dynamic store key can refer to.
+///
+/// See [_Network Entity Keys_] for details.
+///
+/// [_Network Entity Keys_]: https://developer.apple.com/documentation/systemconfiguration/scschemadefinitions?language=objc
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum DynamicStoreEntity {
+    /// Same as `kSCEntNetAirPort`.
+    AirPort,
+    /// Same as `kSCEntNetDHCP`.
+    DHCP,
+    /// Same as `kSCEntNetDNS`.
+    DNS,
+    /// Same as `kSCEntNetEthernet`.
+    Ethernet,
+    /// Same as `kSCEntNetFireWire`.
+    FireWire,
+    /// Same as `kSCEntNetInterface`.
+    Interface,
+    /// Same as `kSCEntNetIPSec`.
+    IPSec,
+    /// Same as `kSCEntNetIPv4`.
+    IPv4,
+    /// Same as `kSCEntNetIPv6`.
+    IPv6,
+    /// Same as `kSCEntNetL2TP`.
+    L2TP,
+    /// Same as `kSCEntNetLink`.
+    Link,
+    /// Same as `kSCEntNetModem`.
+    Modem,
+    /// Same as `kSCEntNetPPP`.
+    PPP,
+    /// Same as `kSCEntNetPPPoE`.
+    PPPoE,
+    /// Same as `kSCEntNetPPPSerial`.
+    PPPSerial,
+    /// Same as `kSCEntNetPPTP`.
+    PPTP,
+    /// Same as `kSCEntNetProxies`.
+    Proxies,
+    /// Same as `kSCEntNetSMB`.
+    SMB,
+    /// Same as `kSCEntNet6to4`.
+    SixToFour,
+    /// Any entity not known by this crate.
+    Other(String),
+}
+
+impl DynamicStoreEntity {
+    /// Returns the entity name as used in dynamic store keys.
+    pub fn as_str(&self) -> &str {
+        match self {
+            DynamicStoreEntity::AirPort => "AirPort",
+            DynamicStoreEntity::DHCP => "DHCP",
+            DynamicStoreEntity::DNS => "DNS",
+            DynamicStoreEntity::Ethernet => "Ethernet",
+            DynamicStoreEntity::FireWire => "FireWire",
+            DynamicStoreEntity::Interface => "Interface",
+            DynamicStoreEntity::IPSec => "IPSec",
+            DynamicStoreEntity::IPv4 => "IPv4",
+            DynamicStoreEntity::IPv6 => "IPv6",
+            DynamicStoreEntity::L2TP => "L2TP",
+            DynamicStoreEntity::Link => "Link",
+            DynamicStoreEntity::Modem => "Modem",
+            DynamicStoreEntity::PPP => "PPP",
+            DynamicStoreEntity::PPPoE => "PPPoE",
+            DynamicStoreEntity::PPPSerial => "PPPSerial",
+            DynamicStoreEntity::PPTP => "PPTP",
+            DynamicStoreEntity::Proxies => "Proxies",
+            DynamicStoreEntity::SMB => "SMB",
+            DynamicStoreEntity::SixToFour => "6to4",
+            DynamicStoreEntity::Other(name) => name,
+        }
+    }
+}
+
+impl From<&str> for DynamicStoreEntity {
+    fn from(name: &str) -> Self {
+        match name {
+            "AirPort" => DynamicStoreEntity::AirPort,
+            "DHCP" => DynamicStoreEntity::DHCP,
+            "DNS" => DynamicStoreEntity::DNS,
+            "Ethernet" => DynamicStoreEntity::Ethernet,
+            "FireWire" => DynamicStoreEntity::FireWire,
+            "Interface" => DynamicStoreEntity::Interface,
+            "IPSec" => DynamicStoreEntity::IPSec,
+            "IPv4" => DynamicStoreEntity::IPv4,
+            "IPv6" => DynamicStoreEntity::IPv6,
+            "L2TP" => DynamicStoreEntity::L2TP,
+            "Link" => DynamicStoreEntity::Link,
+            "Modem" => DynamicStoreEntity::Modem,
+            "PPP" => DynamicStoreEntity::PPP,
+            "PPPoE" => DynamicStoreEntity::PPPoE,
+            "PPPSerial" => DynamicStoreEntity::PPPSerial,
+            "PPTP" => DynamicStoreEntity::PPTP,
+            "Proxies" => DynamicStoreEntity::Proxies,
+            "SMB" => DynamicStoreEntity::SMB,
+            "6to4" => DynamicStoreEntity::SixToFour,
+            other => DynamicStoreEntity::Other(other.to_owned()),
+        }
+    }
+}
+
+impl Display for DynamicStoreEntity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A key to a network related value in the dynamic store.
+///
+/// Formats to, and parses from, the canonical key string. It can be passed directly to the
+/// [`SCDynamicStore`] methods taking a key:
+///
+/// ```no_run
+/// # use system_configuration::dynamic_store::{
+/// #     DynamicStoreDomain, DynamicStoreEntity, DynamicStoreKey, SCDynamicStoreBuilder,
+/// # };
+/// let store = SCDynamicStoreBuilder::new("my-store").build().unwrap();
+/// let key = DynamicStoreKey::global(DynamicStoreDomain::State, DynamicStoreEntity::IPv4);
+/// assert_eq!(key.to_string(), "State:/Network/Global/IPv4");
+/// let ipv4 = store.get(&key);
+/// ```
+///
+/// Service IDs, interface names and entity names must not contain `/`, or the formatted key
+/// will not parse back into the same `DynamicStoreKey`.
+///
+/// [`SCDynamicStore`]: struct.SCDynamicStore.html
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DynamicStoreKey {
+    /// The domain the key lives in.
+    pub domain: DynamicStoreDomain,
+    /// The global, service or interface scope of the key.
+    pub scope: DynamicStoreScope,
+    /// The entity the key refers to. `None` for keys referring to an entire service or interface.
+    pub entity: Option<DynamicStoreEntity>,
+}
+
+impl DynamicStoreKey {
+    /// Creates a key to a global network entity. Equivalent to
+    /// `SCDynamicStoreKeyCreateNetworkGlobalEntity`.
+    pub fn global(domain: DynamicStoreDomain, entity: DynamicStoreEntity) -> Self {
+        DynamicStoreKey {
+            domain,
+            scope: DynamicStoreScope::Global,
+            entity: Some(entity),
+        }
+    }
+
+    /// Creates a key to an entity of the given network service, or to the service itself if
+    /// `entity` is `None`. Equivalent to `SCDynamicStoreKeyCreateNetworkServiceEntity`.
+    pub fn service<S: Into<String>>(
+        domain: DynamicStoreDomain,
+        service_id: S,
+        entity: Option<DynamicStoreEntity>,
+    ) -> Self {
+        DynamicStoreKey {
+            domain,
+            scope: DynamicStoreScope::Service(service_id.into()),
+            entity,
+        }
+    }
+
+    /// Creates a key to an entity of the given interface, or to the interface itself if
+    /// `entity` is `None`. Equivalent to `SCDynamicStoreKeyCreateNetworkInterfaceEntity`.
+    pub fn interface<S: Into<String>>(
+        domain: DynamicStoreDomain,
+        interface_name: S,
+        entity: Option<DynamicStoreEntity>,
+    ) -> Self {
+        DynamicStoreKey {
+            domain,
+            scope: DynamicStoreScope::Interface(interface_name.into()),
+            entity,
+        }
+    }
+
+    /// Returns the service ID or interface name of this key. `None` for global keys.
+    pub fn id(&self) -> Option<&str> {
+        self.scope.id()
+    }
+}
+
+impl Display for DynamicStoreKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}{}{}{}{}",
+            self.domain,
+            SEPARATOR,
+            NETWORK_COMPONENT,
+            SEPARATOR,
+            self.scope.as_str()
+        )?;
+        if let Some(id) = self.scope.id() {
+            write!(f, "{}{}", SEPARATOR, id)?;
+        }
+        if let Some(entity) = &self.entity {
+            write!(f, "{}{}", SEPARATOR, entity)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for DynamicStoreKey {
+    type Err = ParseKeyError;
+
+    fn from_str(key: &str) -> Result<Self, Self::Err> {
+        let parse_error = |kind| ParseKeyError {
+            key: key.to_owned(),
+            kind,
+        };
+
+        let domain_end = key
+            .find(':')
+            .ok_or_else(|| parse_error(ParseKeyErrorKind::UnknownDomain))?;
+        let domain = DynamicStoreDomain::from_str_prefix(&key[..=domain_end])
+            .ok_or_else(|| parse_error(ParseKeyErrorKind::UnknownDomain))?;
+
+        let mut components = key[domain_end + 1..].split(SEPARATOR);
+        if components.next() != Some("") || components.next() != Some(NETWORK_COMPONENT) {
+            return Err(parse_error(ParseKeyErrorKind::NotNetworkKey));
+        }
+
+        let mut next_component = || match components.next() {
+            Some("") => Err(parse_error(ParseKeyErrorKind::EmptyComponent)),
+            component => Ok(component),
+        };
+        let scope = match next_component()? {
+            Some("Global") => DynamicStoreScope::Global,
+            Some("Service") => DynamicStoreScope::Service(
+                next_component()?
+                    .ok_or_else(|| parse_error(ParseKeyErrorKind::MissingId))?
+                    .to_owned(),
+            ),
+            Some("Interface") => DynamicStoreScope::Interface(
+                next_component()?
+                    .ok_or_else(|| parse_error(ParseKeyErrorKind::MissingId))?
+                    .to_owned(),
+            ),
+            _ => return Err(parse_error(ParseKeyErrorKind::UnknownScope)),
+        };
+        let entity = next_component()?.map(DynamicStoreEntity::from);
+        if scope == DynamicStoreScope::Global && entity.is_none() {
+            return Err(parse_error(ParseKeyErrorKind::MissingEntity));
+        }
+        if next_component()?.is_some() {
+            return Err(parse_error(ParseKeyErrorKind::TrailingComponents));
+        }
+
+        Ok(DynamicStoreKey {
+            domain,
+            scope,
+            entity,
+        })
+    }
+}
+
+impl TryFrom<&CFString> for DynamicStoreKey {
+    type Error = ParseKeyError;
+
+    fn try_from(key: &CFString) -> Result<Self, Self::Error> {
+        key.to_string().parse()
+    }
+}
+
+impl From<&DynamicStoreKey> for CFString {
+    fn from(key: &DynamicStoreKey) -> Self {
+        CFString::new(&key.to_string())
+    }
+}
+
+impl From<DynamicStoreKey> for CFString {
+    fn from(key: DynamicStoreKey) -> Self {
+        CFString::from(&key)
+    }
+}
+
+/// Failure to parse a string into a [`DynamicStoreKey`].
+///
+/// [`DynamicStoreKey`]: struct.DynamicStoreKey.html
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseKeyError {
+    key: String,
+    kind: ParseKeyErrorKind,
+}
+
+impl ParseKeyError {
+    /// Returns the key that failed to parse.
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Returns the reason the key failed to parse.
+    pub fn kind(&self) -> ParseKeyErrorKind {
+        self.kind
+    }
+}
+
+impl Display for ParseKeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Invalid dynamic store key \"{}\": {}",
+            self.key, self.kind
+        )
+    }
+}
+
+impl Error for ParseKeyError {}
+
+/// The reasons a string can fail to parse into a [`DynamicStoreKey`].
+///
+/// [`DynamicStoreKey`]: struct.DynamicStoreKey.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseKeyErrorKind {
+    /// The key does not start with a known domain, such as `State:`.
+    UnknownDomain,
+    /// The key is not below `/Network/` in its domain.
+    NotNetworkKey,
+    /// The component after `/Network/` is not `Global`, `Service` or `Interface`.
+    UnknownScope,
+    /// A service or interface key has no service ID or interface name.
+    MissingId,
+    /// A global key has no entity.
+    MissingEntity,
+    /// The key contains an empty component, such as in `State:/Network//DNS`.
+    EmptyComponent,
+    /// The key has more components after the entity.
+    TrailingComponents,
+}
+
+impl Display for ParseKeyErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let description = match self {
+            ParseKeyErrorKind::UnknownDomain => "unknown domain",
+            ParseKeyErrorKind::NotNetworkKey => "not a network key",
+            ParseKeyErrorKind::UnknownScope => "unknown scope",
+            ParseKeyErrorKind::MissingId => "missing service ID or interface name",
+            ParseKeyErrorKind::MissingEntity => "missing entity",
+            ParseKeyErrorKind::EmptyComponent => "empty component",
+            ParseKeyErrorKind::TrailingComponents => "unexpected components after the entity",
+        };
+        f.write_str(description)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SERVICE_ID: &str = "8A2D5E5A-2F34-4C2B-9B5F-3D1B6A7C9E10";
+
+    #[test]
+    fn format_keys() {
+        assert_eq!(
+            DynamicStoreKey::global(DynamicStoreDomain::State, DynamicStoreEntity::IPv4)
+                .to_string(),
+            "State:/Network/Global/IPv4"
+        );
+        assert_eq!(
+            DynamicStoreKey::service(
+                DynamicStoreDomain::Setup,
+                SERVICE_ID,
+                Some(DynamicStoreEntity::DNS)
+            )
+            .to_string(),
+            format!("Setup:/Network/Service/{}/DNS", SERVICE_ID)
+        );
+        assert_eq!(
+            DynamicStoreKey::interface(DynamicStoreDomain::State, "en0", None).to_string(),
+            "State:/Network/Interface/en0"
+        );
+        assert_eq!(
+            DynamicStoreKey::interface(
+                DynamicStoreDomain::Plugin,
+                "utun3",
+                Some(DynamicStoreEntity::SixToFour)
+            )
+            .to_string(),
+            "Plugin:/Network/Interface/utun3/6to4"
+        );
+    }
+
+    #[test]
+    fn parse_keys() {
+        let key: DynamicStoreKey = format!("State:/Network/Service/{}/DNS", SERVICE_ID)
+            .parse()
+            .unwrap();
+        assert_eq!(key.domain, DynamicStoreDomain::State);
+        assert_eq!(key.scope, DynamicStoreScope::Service(SERVICE_ID.to_owned()));
+        assert_eq!(key.id(), Some(SERVICE_ID));
+        assert_eq!(key.entity, Some(DynamicStoreEntity::DNS));
+
+        let key: DynamicStoreKey = "Prefs:/Network/Interface/en0/AirPortCustom"
+            .parse()
+            .unwrap();
+        assert_eq!(key.domain, DynamicStoreDomain::Prefs);
+        assert_eq!(key.id(), Some("en0"));
+        assert_eq!(
+            key.entity,
+            Some(DynamicStoreEntity::Other("AirPortCustom".to_owned()))
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        let keys = [
+            "Setup:/Network/Global/IPv4",
+            "State:/Network/Global/Proxies",
+            "File:/Network/Service/abc",
+            "State:/Network/Service/abc/IPv6",
+            "Plugin:/Network/Interface/en1/Link",
+            "Prefs:/Network/Interface/bridge0",
+        ];
+        for key in keys.iter() {
+            assert_eq!(&key.parse::<DynamicStoreKey>().unwrap().to_string(), key);
+        }
+    }
+
+    #[test]
+    fn parse_errors() {
+        let cases = [
+            ("Network/Global/IPv4", ParseKeyErrorKind::UnknownDomain),
+            (
+                "Unknown:/Network/Global/IPv4",
+                ParseKeyErrorKind::UnknownDomain,
+            ),
+            ("State:/Users/ConsoleUser", ParseKeyErrorKind::NotNetworkKey),
+            (
+                "State:Network/Global/IPv4",
+                ParseKeyErrorKind::NotNetworkKey,
+            ),
+            ("State:/Network/HostNames", ParseKeyErrorKind::UnknownScope),
+            ("State:/Network", ParseKeyErrorKind::UnknownScope),
+            ("State:/Network/Service", ParseKeyErrorKind::MissingId),
+            ("State:/Network/Global", ParseKeyErrorKind::MissingEntity),
+            (
+                "State:/Network/Service//DNS",
+                ParseKeyErrorKind::EmptyComponent,
+            ),
+            (
+                "State:/Network/Global/DNS/",
+                ParseKeyErrorKind::EmptyComponent,
+            ),
+            (
+                "State:/Network/Interface/en0/Link/Extra",
+                ParseKeyErrorKind::TrailingComponents,
+            ),
+        ];
+        for (key, kind) in cases.iter() {
+            let error = key.parse::<DynamicStoreKey>().unwrap_err();
+            assert_eq!(error.kind(), *kind, "{}", key);
+            assert_eq!(error.key(), *key);
+        }
+    }
+}
@@ -0,0 +1,631 @@
+//! Validated key patterns for the dynamic store, and matching keys against them without configd.
+//!
+//! The dynamic store matches keys against POSIX extended regular expressions. Patterns created
+//! with [`DynamicStorePatternBuilder`] escape all literal parts and are anchored at both ends, so
+//! they never match more keys than intended. Patterns written by hand can be checked with
+//! [`DynamicStorePattern::new`] before they are registered. Patterns are compiled and matched with
+//! `regcomp` and `regexec`, like configd does, so they are accepted and match exactly like in the
+//! dynamic store.
+//!
+//! [`DynamicStorePatternBuilder`]: struct.DynamicStorePatternBuilder.html
+//! [`DynamicStorePattern::new`]: struct.DynamicStorePattern.html#method.new
+
+use super::key::{DynamicStoreDomain, DynamicStoreEntity};
+use crate::sys::libc::{
+    c_int, regcomp, regex_t, regexec, regfree, REG_BADBR, REG_BADPAT, REG_BADRPT, REG_EBRACE,
+    REG_EBRACK, REG_ECOLLATE, REG_ECTYPE, REG_EESCAPE, REG_EMPTY, REG_EPAREN, REG_ERANGE,
+    REG_ESPACE, REG_ESUBREG, REG_EXTENDED, REG_NOSUB,
+};
+use core_foundation::string::CFString;
+use std::{
+    error::Error,
+    ffi::CString,
+    fmt::{self, Display},
+    mem, ptr,
+    sync::Arc,
+};
+
+/// Matches a single key component. Same as `kSCCompAnyRegex`.
+const ANY_COMPONENT: &str = "[^/]+";
+
+/// All characters with a special meaning in a POSIX extended regular expression.
+const SPECIAL_CHARACTERS: &[char] = &[
+    '.', '[', ']', '\\', '(', ')', '*', '+', '?', '{', '}', '|', '^', '$',
+];
+
+/// Escapes all characters in `literal` that have a special meaning in a POSIX extended regular
+/// expression, so the result matches `literal` exactly.
+pub fn escape_pattern(literal: &str) -> String {
+    let mut escaped = String::with_capacity(literal.len());
+    for c in literal.chars() {
+        if SPECIAL_CHARACTERS.contains(&c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// A POSIX extended regular expression that is known to be valid, used to match keys in the
+/// dynamic store.
+///
+/// It can be passed directly to the [`SCDynamicStore`] methods taking a pattern, and be tested
+/// against keys without going through the dynamic store:
+///
+/// ```
+/// # use system_configuration::dynamic_store::{
+/// #     DynamicStoreDomain, DynamicStoreEntity, DynamicStorePattern, PatternScope,
+/// # };
+/// let any_service_dns = DynamicStorePattern::builder(PatternScope::AnyService)
+///     .domain(DynamicStoreDomain::State)
+///     .entity(DynamicStoreEntity::DNS)
+///     .build();
+/// assert_eq!(any_service_dns.as_str(), "^State:/Network/Service/[^/]+/DNS$");
+/// assert!(any_service_dns.is_match("State:/Network/Service/1234/DNS"));
+/// assert!(!any_service_dns.is_match("Setup:/Network/Service/1234/DNS"));
+/// ```
+///
+/// [`SCDynamicStore`]: struct.SCDynamicStore.html
+#[derive(Clone)]
+pub struct DynamicStorePattern {
+    pattern: String,
+    regex: Arc<Regex>,
+}
+
+impl DynamicStorePattern {
+    /// Validates `pattern` as a POSIX extended regular expression.
+    pub fn new<S: Into<String>>(pattern: S) -> Result<Self, PatternError> {
+        let pattern = pattern.into();
+        match Regex::new(&pattern) {
+            Ok(regex) => Ok(DynamicStorePattern {
+                pattern,
+                regex: Arc::new(regex),
+            }),
+            Err(kind) => Err(PatternError { pattern, kind }),
+        }
+    }
+
+    /// Returns a builder for a pattern matching network keys in the given scope.
+    pub fn builder(scope: PatternScope) -> DynamicStorePatternBuilder {
+        let entity = match scope {
+            PatternScope::Global => PatternEntity::Any,
+            _ => PatternEntity::None,
+        };
+        DynamicStorePatternBuilder {
+            domain: None,
+            scope,
+            entity,
+        }
+    }
+
+    /// Returns the regular expression.
+    pub fn as_str(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Returns `true` if `key` matches this pattern. Like in the dynamic store, the pattern
+    /// matches anywhere in the key unless it is anchored with `^` and `$`.
+    pub fn is_match(&self, key: &str) -> bool {
+        self.regex.is_match(key)
+    }
+
+    /// Returns the keys that match this pattern.
+    pub fn filter<'a, I>(&'a self, keys: I) -> impl Iterator<Item = I::Item> + 'a
+    where
+        I: IntoIterator,
+        I::IntoIter: 'a,
+        I::Item: AsRef<str>,
+    {
+        keys.into_iter()
+            .filter(move |key| self.is_match(key.as_ref()))
+    }
+}
+
+impl fmt::Debug for DynamicStorePattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DynamicStorePattern")
+            .field("pattern", &self.pattern)
+            .finish()
+    }
+}
+
+impl Display for DynamicStorePattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.pattern)
+    }
+}
+
+impl PartialEq for DynamicStorePattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.pattern == other.pattern
+    }
+}
+
+impl Eq for DynamicStorePattern {}
+
+impl From<&DynamicStorePattern> for CFString {
+    fn from(pattern: &DynamicStorePattern) -> Self {
+        CFString::new(&pattern.pattern)
+    }
+}
+
+impl From<DynamicStorePattern> for CFString {
+    fn from(pattern: DynamicStorePattern) -> Self {
+        CFString::from(&pattern)
+    }
+}
+
+/// The network keys a [`DynamicStorePattern`] built by [`DynamicStorePatternBuilder`] matches.
+///
+/// [`DynamicStorePattern`]: struct.DynamicStorePattern.html
+/// [`DynamicStorePatternBuilder`]: struct.DynamicStorePatternBuilder.html
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PatternScope {
+    /// Global keys, such as `State:/Network/Global/IPv4`.
+    Global,
+    /// Keys of any network service.
+    AnyService,
+    /// Keys of the network service with the given service ID.
+    Service(String),
+    /// Keys of any interface.
+    AnyInterface,
+    /// Keys of the interface with the given _BSD_ name, such as `en0`.
+    Interface(String),
+}
+
+#[derive(Debug, Clone)]
+enum PatternEntity {
+    None,
+    Any,
+    Entity(DynamicStoreEntity),
+}
+
+/// Builder for [`DynamicStorePattern`]s matching network keys.
+///
+/// Matches keys in all domains by default. Keys without an entity are matched by default too,
+/// except in [`PatternScope::Global`], where every key has an entity, so any entity is matched
+/// instead.
+///
+/// [`PatternScope::Global`]: enum.PatternScope.html#variant.Global
+///
+/// [`DynamicStorePattern`]: struct.DynamicStorePattern.html
+#[derive(Debug, Clone)]
+pub struct DynamicStorePatternBuilder {
+    domain: Option<DynamicStoreDomain>,
+    scope: PatternScope,
+    entity: PatternEntity,
+}
+
+impl DynamicStorePatternBuilder {
+    /// Only match keys in the given domain.
+    pub fn domain(mut self, domain: DynamicStoreDomain) -> Self {
+        self.domain = Some(domain);
+        self
+    }
+
+    /// Only match keys referring to the given entity.
+    pub fn entity(mut self, entity: DynamicStoreEntity) -> Self {
+        self.entity = PatternEntity::Entity(entity);
+        self
+    }
+
+    /// Match keys referring to any entity, but not keys without an entity.
+    pub fn any_entity(mut self) -> Self {
+        self.entity = PatternEntity::Any;
+        self
+    }
+
+    /// Creates the pattern.
+    pub fn build(self) -> DynamicStorePattern {
+        let mut pattern = String::from("^");
+        match self.domain {
+            Some(domain) => pattern.push_str(&escape_pattern(domain.as_str())),
+            None => pattern.push_str("(Setup:|State:|File:|Plugin:|Prefs:)"),
+        }
+        pattern.push_str("/Network/");
+        match &self.scope {
+            PatternScope::Global => pattern.push_str("Global"),
+            PatternScope::AnyService => pattern.push_str(&format!("Service/{}", ANY_COMPONENT)),
+            PatternScope::Service(id) => {
+                pattern.push_str(&format!("Service/{}", escape_pattern(id)))
+            }
+            PatternScope::AnyInterface => pattern.push_str(&format!("Interface/{}", ANY_COMPONENT)),
+            PatternScope::Interface(name) => {
+                pattern.push_str(&format!("Interface/{}", escape_pattern(name)))
+            }
+        }
+        match &self.entity {
+            PatternEntity::None => (),
+            PatternEntity::Any => pattern.push_str(&format!("/{}", ANY_COMPONENT)),
+            PatternEntity::Entity(entity) => {
+                pattern.push_str(&format!("/{}", escape_pattern(entity.as_str())))
+            }
+        }
+        pattern.push('$');
+
+        DynamicStorePattern::new(pattern).expect("Escaped pattern is always valid")
+    }
+}
+
+/// Failure to validate a [`DynamicStorePattern`].
+///
+/// [`DynamicStorePattern`]: struct.DynamicStorePattern.html
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatternError {
+    pattern: String,
+    kind: PatternErrorKind,
+}
+
+impl PatternError {
+    /// Returns the invalid pattern.
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Returns the reason the pattern is invalid.
+    pub fn kind(&self) -> PatternErrorKind {
+        self.kind
+    }
+}
+
+impl Display for PatternError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Invalid dynamic store pattern \"{}\": {}",
+            self.pattern, self.kind
+        )
+    }
+}
+
+impl Error for PatternError {}
+
+/// The reasons a pattern can be invalid, as reported by `regcomp`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PatternErrorKind {
+    /// The pattern is invalid, or contains a NUL character. Same as `REG_BADPAT`.
+    InvalidPattern,
+    /// A collating element is invalid. Same as `REG_ECOLLATE`.
+    InvalidCollatingElement,
+    /// A bracket expression names an unknown character class. Same as `REG_ECTYPE`.
+    UnknownCharacterClass,
+    /// The pattern ends with a single backslash. Same as `REG_EESCAPE`.
+    TrailingBackslash,
+    /// A back reference refers to a group that does not exist. Same as `REG_ESUBREG`.
+    InvalidBackReference,
+    /// A bracket expression is not closed. Same as `REG_EBRACK`.
+    UnmatchedBracket,
+    /// A parenthesis is not matched. Same as `REG_EPAREN`.
+    UnmatchedParenthesis,
+    /// A brace is not matched. Same as `REG_EBRACE`.
+    UnmatchedBrace,
+    /// A bound such as `{1,3}` is malformed or too large. Same as `REG_BADBR`.
+    InvalidBound,
+    /// A range in a bracket expression ends before it starts. Same as `REG_ERANGE`.
+    InvalidRange,
+    /// Compiling the pattern ran out of memory. Same as `REG_ESPACE`.
+    TooComplex,
+    /// A repetition operator has nothing to repeat. Same as `REG_BADRPT`.
+    MissingRepetitionOperand,
+    /// The pattern, a group or an alternative is empty. Same as `REG_EMPTY`.
+    EmptyExpression,
+    /// An error code without a variant in this enum.
+    Other(i32),
+}
+
+/// The known `regcomp` error codes and their descriptions.
+const KNOWN_ERRORS: &[(PatternErrorKind, c_int, &str)] = &[
+    (
+        PatternErrorKind::InvalidPattern,
+        REG_BADPAT,
+        "invalid pattern",
+    ),
+    (
+        PatternErrorKind::InvalidCollatingElement,
+        REG_ECOLLATE,
+        "invalid collating element",
+    ),
+    (
+        PatternErrorKind::UnknownCharacterClass,
+        REG_ECTYPE,
+        "unknown character class",
+    ),
+    (
+        PatternErrorKind::TrailingBackslash,
+        REG_EESCAPE,
+        "trailing backslash",
+    ),
+    (
+        PatternErrorKind::InvalidBackReference,
+        REG_ESUBREG,
+        "invalid back reference",
+    ),
+    (
+        PatternErrorKind::UnmatchedBracket,
+        REG_EBRACK,
+        "unmatched bracket",
+    ),
+    (
+        PatternErrorKind::UnmatchedParenthesis,
+        REG_EPAREN,
+        "unmatched parenthesis",
+    ),
+    (
+        PatternErrorKind::UnmatchedBrace,
+        REG_EBRACE,
+        "unmatched brace",
+    ),
+    (
+        PatternErrorKind::InvalidBound,
+        REG_BADBR,
+        "invalid repetition bound",
+    ),
+    (
+        PatternErrorKind::InvalidRange,
+        REG_ERANGE,
+        "invalid character range",
+    ),
+    (
+        PatternErrorKind::TooComplex,
+        REG_ESPACE,
+        "pattern too complex",
+    ),
+    (
+        PatternErrorKind::MissingRepetitionOperand,
+        REG_BADRPT,
+        "repetition operator without operand",
+    ),
+    (
+        PatternErrorKind::EmptyExpression,
+        REG_EMPTY,
+        "empty expression",
+    ),
+];
+
+impl PatternErrorKind {
+    fn from_code(code: c_int) -> Self {
+        KNOWN_ERRORS
+            .iter()
+            .find(|(_, known_code, _)| *known_code == code)
+            .map_or(PatternErrorKind::Other(code), |(kind, _, _)| *kind)
+    }
+}
+
+impl Display for PatternErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PatternErrorKind::Other(code) => write!(f, "regcomp error {}", code),
+            kind => {
+                let description = KNOWN_ERRORS
+                    .iter()
+                    .find(|(known_kind, _, _)| known_kind == kind)
+                    .map_or("unknown error", |(_, _, description)| description);
+                f.write_str(description)
+            }
+        }
+    }
+}
+
+/// A pattern compiled with `regcomp`, the same way configd compiles the patterns it matches keys
+/// against.
+struct Regex(Box<regex_t>);
+
+// `regexec` does not modify the compiled pattern, so it can be used from several threads at once.
+unsafe impl Send for Regex {}
+unsafe impl Sync for Regex {}
+
+impl Regex {
+    fn new(pattern: &str) -> Result<Self, PatternErrorKind> {
+        let pattern = CString::new(pattern).map_err(|_| PatternErrorKind::InvalidPattern)?;
+        let mut regex = Box::new(unsafe { mem::zeroed::<regex_t>() });
+        let code = unsafe { regcomp(&mut *regex, pattern.as_ptr(), REG_EXTENDED | REG_NOSUB) };
+        if code == 0 {
+            Ok(Regex(regex))
+        } else {
+            Err(PatternErrorKind::from_code(code))
+        }
+    }
+
+    fn is_match(&self, key: &str) -> bool {
+        // Keys are CFStrings converted to C strings by configd, so they never contain NUL.
+        let key = match CString::new(key) {
+            Ok(key) => key,
+            Err(_) => return false,
+        };
+        unsafe { regexec(&*self.0, key.as_ptr(), 0, ptr::null_mut(), 0) == 0 }
+    }
+}
+
+impl Drop for Regex {
+    fn drop(&mut self) {
+        unsafe { regfree(&mut *self.0) };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escape() {
+        assert_eq!(escape_pattern("en0"), "en0");
+        assert_eq!(
+            escape_pattern("com.example.vpn(1)"),
+            "com\\.example\\.vpn\\(1\\)"
+        );
+        assert_eq!(escape_pattern("a+b*c?|^$"), "a\\+b\\*c\\?\\|\\^\\$");
+        assert_eq!(escape_pattern("[x]{1}\\"), "\\[x\\]\\{1\\}\\\\");
+
+        let literal = "weird.[name]+(1)|$";
+        let pattern = DynamicStorePattern::new(format!("^{}$", escape_pattern(literal))).unwrap();
+        assert!(pattern.is_match(literal));
+        assert!(!pattern.is_match("weirdX[name]+(1)|$"));
+    }
+
+    #[test]
+    fn build_patterns() {
+        let pattern = DynamicStorePattern::builder(PatternScope::AnyService)
+            .entity(DynamicStoreEntity::DNS)
+            .build();
+        assert_eq!(
+            pattern.as_str(),
+            "^(Setup:|State:|File:|Plugin:|Prefs:)/Network/Service/[^/]+/DNS$"
+        );
+
+        let pattern = DynamicStorePattern::builder(PatternScope::Interface("en0".to_owned()))
+            .domain(DynamicStoreDomain::State)
+            .entity(DynamicStoreEntity::IPv4)
+            .build();
+        assert_eq!(pattern.as_str(), "^State:/Network/Interface/en0/IPv4$");
+
+        let pattern = DynamicStorePattern::builder(PatternScope::Global)
+            .domain(DynamicStoreDomain::Setup)
+            .any_entity()
+            .build();
+        assert_eq!(pattern.as_str(), "^Setup:/Network/Global/[^/]+$");
+
+        let pattern = DynamicStorePattern::builder(PatternScope::Global)
+            .domain(DynamicStoreDomain::State)
+            .build();
+        assert_eq!(pattern.as_str(), "^State:/Network/Global/[^/]+$");
+        assert!(pattern.is_match("State:/Network/Global/IPv4"));
+        assert!(!pattern.is_match("State:/Network/Global"));
+
+        let pattern = DynamicStorePattern::builder(PatternScope::Service("a.b".to_owned()))
+            .entity(DynamicStoreEntity::Other("com.example".to_owned()))
+            .build();
+        assert!(pattern.is_match("State:/Network/Service/a.b/com.example"));
+        assert!(!pattern.is_match("State:/Network/Service/aXb/com.example"));
+        assert!(!pattern.is_match("State:/Network/Service/a.b/comXexample"));
+    }
+
+    #[test]
+    fn match_built_patterns() {
+        let keys = [
+            "State:/Network/Global/IPv4",
+            "State:/Network/Service/1234/DNS",
+            "Setup:/Network/Service/1234/DNS",
+            "State:/Network/Service/1234/IPv4",
+            "State:/Network/Service/1234",
+            "State:/Network/Interface/en0/IPv4",
+            "State:/Network/Interface/en0/Link",
+            "State:/Network/Interface/en10/IPv4",
+            "State:/Network/Service/1234/DNS/Extra",
+        ];
+
+        let any_service_dns = DynamicStorePattern::builder(PatternScope::AnyService)
+            .entity(DynamicStoreEntity::DNS)
+            .build();
+        assert_eq!(
+            any_service_dns.filter(keys.iter()).collect::<Vec<_>>(),
+            [
+                &"State:/Network/Service/1234/DNS",
+                &"Setup:/Network/Service/1234/DNS"
+            ]
+        );
+
+        let en0_ipv4 = DynamicStorePattern::builder(PatternScope::Interface("en0".to_owned()))
+            .domain(DynamicStoreDomain::State)
+            .entity(DynamicStoreEntity::IPv4)
+            .build();
+        assert_eq!(
+            en0_ipv4.filter(keys.iter()).collect::<Vec<_>>(),
+            [&"State:/Network/Interface/en0/IPv4"]
+        );
+
+        let service = DynamicStorePattern::builder(PatternScope::Service("1234".to_owned()))
+            .domain(DynamicStoreDomain::State)
+            .build();
+        assert_eq!(
+            service.filter(keys.iter()).collect::<Vec<_>>(),
+            [&"State:/Network/Service/1234"]
+        );
+    }
+
+    #[test]
+    fn match_regular_expressions() {
+        let cases: &[(&str, &str, bool)] = &[
+            ("DNS", "State:/Network/Global/DNS", true),
+            ("^DNS", "State:/Network/Global/DNS", false),
+            (
+                "(State|Setup):/Network/Service/.*/DNS",
+                "Setup:/Network/Service/x/DNS",
+                true,
+            ),
+            (
+                "(State|Setup):/Network/Service/.*/DNS",
+                "File:/Network/Service/x/DNS",
+                false,
+            ),
+            ("^en[0-9]+$", "en12", true),
+            ("^en[0-9]+$", "en", false),
+            ("^en[^0-9]$", "enx", true),
+            ("^en[^0-9]$", "en1", false),
+            ("^a{2,3}$", "aa", true),
+            ("^a{2,3}$", "aaaa", false),
+            ("^a{2}$", "aa", true),
+            ("^a{2,}$", "aaaaa", true),
+            ("^(ab)?c$", "c", true),
+            ("^(ab)?c$", "abc", true),
+            ("^(a*)*b$", "aaab", true),
+            ("^(a|b)*$", "abba", true),
+            ("^[[:digit:][:upper:]]+$", "A1B2", true),
+            ("^[[:digit:][:upper:]]+$", "A1b2", false),
+            ("^[]a]$", "]", true),
+            ("^[a-]$", "-", true),
+            ("^[[.-.]]$", "-", true),
+            ("^\\.$", ".", true),
+            ("^\\.$", "x", false),
+        ];
+        for (pattern, key, expected) in cases.iter() {
+            let regex = match DynamicStorePattern::new(*pattern) {
+                Ok(regex) => regex,
+                Err(error) => panic!("{}", error),
+            };
+            assert_eq!(regex.is_match(key), *expected, "{} =~ {}", key, pattern);
+        }
+    }
+
+    #[test]
+    fn invalid_patterns() {
+        let cases = [
+            ("(State", PatternErrorKind::UnmatchedParenthesis),
+            ("[^/", PatternErrorKind::UnmatchedBracket),
+            ("[z-a]", PatternErrorKind::InvalidRange),
+            ("[[:word:]]", PatternErrorKind::UnknownCharacterClass),
+            ("abc\\", PatternErrorKind::TrailingBackslash),
+            ("a\0b", PatternErrorKind::InvalidPattern),
+        ];
+        for (pattern, kind) in cases.iter() {
+            let error = DynamicStorePattern::new(*pattern).unwrap_err();
+            assert_eq!(error.kind(), *kind, "{}", pattern);
+            assert_eq!(error.pattern(), *pattern);
+        }
+        assert_eq!(
+            DynamicStorePattern::new("(State").unwrap_err().to_string(),
+            "Invalid dynamic store pattern \"(State\": unmatched parenthesis"
+        );
+    }
+
+    #[test]
+    fn match_long_keys() {
+        let key = "a".repeat(10_000);
+        let pattern = DynamicStorePattern::new(".*x").unwrap();
+        assert!(!pattern.is_match(&key));
+        assert!(pattern.is_match(&format!("{}x", key)));
+
+        let pattern = DynamicStorePattern::new("^[^/]+$").unwrap();
+        assert!(pattern.is_match(&key));
+    }
+
+    #[test]
+    fn match_nested_repetitions() {
+        let pattern = DynamicStorePattern::new("^(a*)*b$").unwrap();
+        let key = "a".repeat(100);
+        assert!(!pattern.is_match(&key));
+        assert!(pattern.is_match(&format!("{}b", key)));
+
+        let pattern = DynamicStorePattern::new("^((a|aa)+)+$").unwrap();
+        assert!(!pattern.is_match(&format!("{}c", key)));
+    }
+}
@@ -0,0 +1,132 @@
+//! Host information from the dynamic store, such as the computer name and the console user.
+//!
+//! Every function takes an optional store. With `None`, a temporary session is opened for the
+//! single lookup. Values that are not set are returned as `Ok(None)`, and any other failure as the
+//! error reported by the framework. See [`SCDynamicStoreCopySpecific`] for details.
+//!
+//! [`SCDynamicStoreCopySpecific`]: https://developer.apple.com/documentation/systemconfiguration/scdynamicstorecopyspecific?language=objc
+
+use super::SCDynamicStore;
+use crate::{
+    error::SCError,
+    sys::{
+        dynamic_store::SCDynamicStoreRef,
+        dynamic_store_copy_specific::{
+            gid_t, uid_t, SCDynamicStoreCopyComputerName, SCDynamicStoreCopyConsoleUser,
+            SCDynamicStoreCopyLocalHostName, SCDynamicStoreCopyLocation,
+        },
+    },
+};
+use core_foundation::{
+    base::TCFType,
+    string::{kCFStringEncodingUTF8, CFString, CFStringEncoding, CFStringRef},
+};
+use std::ptr;
+
+/// The computer name, as shown to users.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ComputerName {
+    /// The name of the computer.
+    pub name: String,
+    /// The encoding the name should be converted to for legacy protocols, such as NetBIOS.
+    pub encoding: CFStringEncoding,
+}
+
+/// The user currently logged in at the console.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ConsoleUser {
+    /// The short name of the user.
+    pub name: String,
+    /// The user ID of the user.
+    pub uid: uid_t,
+    /// The primary group ID of the user.
+    pub gid: gid_t,
+}
+
+/// Returns the computer name, or `None` if it is not set.
+///
+/// See [`SCDynamicStoreCopyComputerName`] for details.
+///
+/// [`SCDynamicStoreCopyComputerName`]: https://developer.apple.com/documentation/systemconfiguration/1437828-scdynamicstorecopycomputername?language=objc
+pub fn computer_name(store: Option<&SCDynamicStore>) -> Result<Option<ComputerName>, SCError> {
+    let mut encoding = kCFStringEncodingUTF8;
+    let name = unsafe {
+        wrap_string(SCDynamicStoreCopyComputerName(
+            store_ref(store),
+            &mut encoding,
+        ))
+    }?;
+    Ok(name.map(|name| ComputerName { name, encoding }))
+}
+
+/// Returns the user currently logged in at the console, or `None` if no user is logged in.
+///
+/// See [`SCDynamicStoreCopyConsoleUser`] for details.
+///
+/// [`SCDynamicStoreCopyConsoleUser`]: https://developer.apple.com/documentation/systemconfiguration/1517123-scdynamicstorecopyconsoleuser?language=objc
+pub fn console_user(store: Option<&SCDynamicStore>) -> Result<Option<ConsoleUser>, SCError> {
+    let mut uid = 0;
+    let mut gid = 0;
+    let name = unsafe {
+        wrap_string(SCDynamicStoreCopyConsoleUser(
+            store_ref(store),
+            &mut uid,
+            &mut gid,
+        ))
+    }?;
+    Ok(name.map(|name| ConsoleUser { name, uid, gid }))
+}
+
+/// Returns the local host name, used for Bonjour, without the `.local` suffix. Or `None` if it is
+/// not set.
+///
+/// See [`SCDynamicStoreCopyLocalHostName`] for details.
+///
+/// [`SCDynamicStoreCopyLocalHostName`]: https://developer.apple.com/documentation/systemconfiguration/1517121-scdynamicstorecopylocalhostname?language=objc
+pub fn local_host_name(store: Option<&SCDynamicStore>) -> Result<Option<String>, SCError> {
+    unsafe { wrap_string(SCDynamicStoreCopyLocalHostName(store_ref(store))) }
+}
+
+/// Returns the ID of the current network location, or `None` if it is not set.
+///
+/// See [`SCDynamicStoreCopyLocation`] for details.
+///
+/// [`SCDynamicStoreCopyLocation`]: https://developer.apple.com/documentation/systemconfiguration/1437838-scdynamicstorecopylocation?language=objc
+pub fn location(store: Option<&SCDynamicStore>) -> Result<Option<String>, SCError> {
+    unsafe { wrap_string(SCDynamicStoreCopyLocation(store_ref(store))) }
+}
+
+fn store_ref(store: Option<&SCDynamicStore>) -> SCDynamicStoreRef {
+    store.map_or(ptr::null(), |store| store.as_concrete_TypeRef())
+}
+
+/// Wraps a copied string. A NULL string is `None` if the value is not set, and the error of the
+/// failed call otherwise.
+unsafe fn wrap_string(string_ref: CFStringRef) -> Result<Option<String>, SCError> {
+    if string_ref.is_null() {
+        match SCError::last() {
+            SCError::NoKey => Ok(None),
+            error => Err(error),
+        }
+    } else {
+        Ok(Some(
+            CFString::wrap_under_create_rule(string_ref).to_string(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dynamic_store::SCDynamicStoreBuilder;
+
+    #[test]
+    fn with_and_without_store() {
+        let store = SCDynamicStoreBuilder::new("test").build().unwrap();
+        assert_eq!(computer_name(Some(&store)), computer_name(None));
+        assert_eq!(console_user(Some(&store)), console_user(None));
+        assert_eq!(local_host_name(Some(&store)), local_host_name(None));
+        assert_eq!(location(Some(&store)), location(None));
+        assert!(computer_name(None).is_ok());
+    }
+}
@@ -0,0 +1,199 @@
+//! A [`Stream`] of changes in the dynamic store, for use in async code.
+//!
+//! Requires the `stream` feature.
+//!
+//! [`Stream`]: https://docs.rs/futures-core/0.3/futures_core/stream/trait.Stream.html
+
+use super::{
+    DispatchQueueHandle, DynamicStoreKey, DynamicStorePattern, SCDynamicStore,
+    SCDynamicStoreBuilder,
+};
+use crate::{
+    dispatch::{DispatchQueue, QualityOfService},
+    error::SCError,
+};
+use core_foundation::{array::CFArray, propertylist::CFPropertyList, string::CFString};
+use futures_channel::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use futures_core::Stream;
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// A changed key in the dynamic store, with the value it had right after the change.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DynamicStoreChange {
+    /// The key that changed.
+    pub key: String,
+    /// The new value of the key, or `None` if the key was removed.
+    pub value: Option<CFPropertyList>,
+}
+
+// Property lists read from the dynamic store are immutable, and Core Foundation reference counting
+// is thread safe, so the values can be moved to the thread polling the stream.
+unsafe impl Send for DynamicStoreChange {}
+
+/// Stream of changes to watched keys in the dynamic store. Each item is one batch of changed
+/// keys, as delivered in a single notification, with their new values read atomically. If the
+/// values could not be read, the item is the error instead, and the stream continues with the next
+/// notification.
+///
+/// Notifications are delivered on a dispatch queue owned by the stream, so no run loop is
+/// needed. Dropping the stream unregisters the notification keys and releases the store.
+///
+/// Created by [`SCDynamicStoreBuilder::build_stream`].
+///
+/// [`SCDynamicStoreBuilder::build_stream`]: struct.SCDynamicStoreBuilder.html#method.build_stream
+pub struct DynamicStoreStream {
+    receiver: UnboundedReceiver<Result<Vec<DynamicStoreChange>, SCError>>,
+    store: SCDynamicStore,
+    _handle: DispatchQueueHandle,
+}
+
+// The store is only used to unregister the notification keys when the stream is dropped. The
+// dynamic store session does its own locking, so this can be done from any thread.
+unsafe impl Send for DynamicStoreStream {}
+
+impl SCDynamicStoreBuilder<()> {
+    /// Create the dynamic store session and return a stream of changes to the given `keys`, and
+    /// to all keys matching any of the given `patterns`. Fails if the session could not be created
+    /// or the notification keys could not be set.
+    ///
+    /// ```no_run
+    /// # use futures::StreamExt;
+    /// # use system_configuration::dynamic_store::{
+    /// #     DynamicStoreEntity, DynamicStorePattern, PatternScope, SCDynamicStoreBuilder,
+    /// # };
+    /// # async fn watch() {
+    /// let dns = DynamicStorePattern::builder(PatternScope::AnyService)
+    ///     .entity(DynamicStoreEntity::DNS)
+    ///     .build();
+    /// let mut changes = SCDynamicStoreBuilder::new("my-dns-watcher")
+    ///     .build_stream(&[], &[dns])
+    ///     .unwrap();
+    /// while let Some(batch) = changes.next().await {
+    ///     for change in batch.unwrap() {
+    ///         println!("{} changed to {:?}", change.key, change.value);
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    pub fn build_stream(
+        self,
+        keys: &[DynamicStoreKey],
+        patterns: &[DynamicStorePattern],
+    ) -> Result<DynamicStoreStream, SCError> {
+        let (sender, receiver) = mpsc::unbounded();
+        let queue = DispatchQueue::global(QualityOfService::Default);
+        let (store, handle) = self
+            .callback(move |store: &SCDynamicStore, changed_keys: Vec<String>| {
+                send_changes(&sender, store, changed_keys)
+            })
+            .build_with_dispatch_queue(&queue)?;
+        store.set_notifications(keys, patterns)?;
+        Ok(DynamicStoreStream {
+            receiver,
+            store,
+            _handle: handle,
+        })
+    }
+}
+
+fn send_changes(
+    sender: &UnboundedSender<Result<Vec<DynamicStoreChange>, SCError>>,
+    store: &SCDynamicStore,
+    changed_keys: Vec<String>,
+) {
+    let cf_keys = changed_keys
+        .iter()
+        .map(|key| CFString::new(key))
+        .collect::<Vec<_>>();
+    let changes = store
+        .get_multiple(
+            &CFArray::from_CFTypes(&cf_keys),
+            &CFArray::<CFString>::from_CFTypes(&[]),
+        )
+        .map(|mut values| {
+            changed_keys
+                .into_iter()
+                .map(|key| DynamicStoreChange {
+                    value: values.remove(&key),
+                    key,
+                })
+                .collect()
+        });
+    // The receiver is only gone while the stream is being dropped.
+    let _ = sender.unbounded_send(changes);
+}
+
+impl Stream for DynamicStoreStream {
+    type Item = Result<Vec<DynamicStoreChange>, SCError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.receiver).poll_next(cx)
+    }
+}
+
+impl Drop for DynamicStoreStream {
+    fn drop(&mut self) {
+        // The dispatch queue is detached, and the store released, when the fields are dropped.
+        let _ = self.store.set_notifications(&[], &[]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dynamic_store::{DynamicStoreDomain, DynamicStoreEntity, PatternScope};
+    use futures::{FutureExt, StreamExt};
+    use std::{
+        thread,
+        time::{Duration, Instant},
+    };
+
+    #[test]
+    fn build_and_drop_stream() {
+        let pattern = DynamicStorePattern::builder(PatternScope::Global)
+            .any_entity()
+            .build();
+        let mut stream = SCDynamicStoreBuilder::new("test")
+            .build_stream(&[], &[pattern])
+            .unwrap();
+        // Nothing has changed since the notification keys were registered.
+        assert!(stream.next().now_or_never().is_none());
+    }
+
+    #[test]
+    fn receive_changed_value() {
+        let key = DynamicStoreKey::global(
+            DynamicStoreDomain::State,
+            DynamicStoreEntity::from("system-configuration-rs-stream-test"),
+        );
+        let mut stream = SCDynamicStoreBuilder::new("test")
+            .build_stream(std::slice::from_ref(&key), &[])
+            .unwrap();
+        let store = SCDynamicStoreBuilder::new("test").build().unwrap();
+        store.set(&key, CFString::new("changed")).unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let batch = loop {
+            if let Some(batch) = stream.next().now_or_never() {
+                break batch.unwrap().unwrap();
+            }
+            assert!(Instant::now() < deadline, "no change was received");
+            thread::sleep(Duration::from_millis(10));
+        };
+        store.remove(&key).unwrap();
+
+        assert_eq!(batch.len(), 1);
+        assert_eq!(batch[0].key, key.to_string());
+        let value = batch[0]
+            .value
+            .clone()
+            .and_then(CFPropertyList::downcast_into::<CFString>);
+        assert_eq!(
+            value.map(|value| value.to_string()),
+            Some("changed".to_owned())
+        );
+    }
+}
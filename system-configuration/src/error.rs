@@ -0,0 +1,292 @@
+//! Errors reported by the SystemConfiguration framework.
+//!
+//! Most functions in the framework report failure with a `false` or `NULL` return value, and
+//! leave the reason in a thread local status code, read with [`SCError()`]. [`SCError`] turns
+//! that status code into a Rust error.
+//!
+//! [`SCError()`]: https://developer.apple.com/documentation/systemconfiguration/1516922-scerror?language=objc
+//! [`SCError`]: enum.SCError.html
+
+use crate::sys::system_configuration::{
+    kSCStatusAccessError, kSCStatusConnectionIgnore, kSCStatusConnectionNoService, kSCStatusFailed,
+    kSCStatusInvalidArgument, kSCStatusKeyExists, kSCStatusLocked, kSCStatusMaxLink,
+    kSCStatusNeedLock, kSCStatusNoConfigFile, kSCStatusNoKey, kSCStatusNoLink,
+    kSCStatusNoPrefsSession, kSCStatusNoStoreServer, kSCStatusNoStoreSession,
+    kSCStatusNotifierActive, kSCStatusOK, kSCStatusPrefsBusy, kSCStatusReachabilityUnknown,
+    kSCStatusStale, SCCopyLastError, SCErrorString,
+};
+use core_foundation::{
+    base::{Boolean, TCFType},
+    error::CFError,
+};
+use std::{error::Error, ffi::CStr, fmt};
+
+/// An error reported by SystemConfiguration, identified by its `kSCStatus*` status code.
+///
+/// See [`SCError()`] for details.
+///
+/// [`SCError()`]: https://developer.apple.com/documentation/systemconfiguration/1516922-scerror?language=objc
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SCError {
+    /// `kSCStatusFailed`: Non-specific failure.
+    Failed,
+    /// `kSCStatusInvalidArgument`: Invalid argument.
+    InvalidArgument,
+    /// `kSCStatusAccessError`: Permission denied.
+    AccessError,
+    /// `kSCStatusNoKey`: No such key.
+    NoKey,
+    /// `kSCStatusKeyExists`: Key already defined.
+    KeyExists,
+    /// `kSCStatusLocked`: Lock already held.
+    Locked,
+    /// `kSCStatusNeedLock`: Lock not held.
+    NeedLock,
+    /// `kSCStatusNoStoreSession`: Configuration daemon session not active.
+    NoStoreSession,
+    /// `kSCStatusNoStoreServer`: Configuration daemon not (or no longer) available.
+    NoStoreServer,
+    /// `kSCStatusNotifierActive`: Notifier is currently active.
+    NotifierActive,
+    /// `kSCStatusNoPrefsSession`: Preferences session not active.
+    NoPrefsSession,
+    /// `kSCStatusPrefsBusy`: Preferences update currently in progress.
+    PrefsBusy,
+    /// `kSCStatusNoConfigFile`: Configuration file not found.
+    NoConfigFile,
+    /// `kSCStatusNoLink`: No such link.
+    NoLink,
+    /// `kSCStatusStale`: Write attempted on a stale version of the object.
+    Stale,
+    /// `kSCStatusMaxLink`: Maximum link count exceeded.
+    MaxLink,
+    /// `kSCStatusReachabilityUnknown`: Network reachability cannot be determined.
+    ReachabilityUnknown,
+    /// `kSCStatusConnectionNoService`: Network service for connection not available.
+    ConnectionNoService,
+    /// `kSCStatusConnectionIgnore`: Network connection information not available at this time.
+    ConnectionIgnore,
+    /// A status code without a `kSCStatus*` constant, such as an `errno` or Mach error code.
+    Other(i32),
+}
+
+/// The known errors, with their status codes and messages.
+const KNOWN_ERRORS: [(SCError, i32, &str); 19] = [
+    (SCError::Failed, kSCStatusFailed, "Failed"),
+    (
+        SCError::InvalidArgument,
+        kSCStatusInvalidArgument,
+        "Invalid argument",
+    ),
+    (
+        SCError::AccessError,
+        kSCStatusAccessError,
+        "Permission denied",
+    ),
+    (SCError::NoKey, kSCStatusNoKey, "No such key"),
+    (
+        SCError::KeyExists,
+        kSCStatusKeyExists,
+        "Key already defined",
+    ),
+    (SCError::Locked, kSCStatusLocked, "Lock already held"),
+    (SCError::NeedLock, kSCStatusNeedLock, "Lock not held"),
+    (
+        SCError::NoStoreSession,
+        kSCStatusNoStoreSession,
+        "Configuration daemon session not active",
+    ),
+    (
+        SCError::NoStoreServer,
+        kSCStatusNoStoreServer,
+        "Configuration daemon not (or no longer) available",
+    ),
+    (
+        SCError::NotifierActive,
+        kSCStatusNotifierActive,
+        "Notifier is currently active",
+    ),
+    (
+        SCError::NoPrefsSession,
+        kSCStatusNoPrefsSession,
+        "Preferences session not active",
+    ),
+    (
+        SCError::PrefsBusy,
+        kSCStatusPrefsBusy,
+        "Preferences update currently in progress",
+    ),
+    (
+        SCError::NoConfigFile,
+        kSCStatusNoConfigFile,
+        "Configuration file not found",
+    ),
+    (SCError::NoLink, kSCStatusNoLink, "No such link"),
+    (
+        SCError::Stale,
+        kSCStatusStale,
+        "Write attempted on stale version of object",
+    ),
+    (
+        SCError::MaxLink,
+        kSCStatusMaxLink,
+        "Maximum link count exceeded",
+    ),
+    (
+        SCError::ReachabilityUnknown,
+        kSCStatusReachabilityUnknown,
+        "Network reachability cannot be determined",
+    ),
+    (
+        SCError::ConnectionNoService,
+        kSCStatusConnectionNoService,
+        "Network service for connection not available",
+    ),
+    (
+        SCError::ConnectionIgnore,
+        kSCStatusConnectionIgnore,
+        "Network connection information not available at this time",
+    ),
+];
+
+impl SCError {
+    /// Returns the error for a status code, or `None` for `kSCStatusOK`.
+    pub fn from_code(code: i32) -> Option<Self> {
+        if code == kSCStatusOK {
+            return None;
+        }
+        let error = KNOWN_ERRORS
+            .iter()
+            .find(|(_, known_code, _)| *known_code == code)
+            .map_or(SCError::Other(code), |(error, _, _)| *error);
+        Some(error)
+    }
+
+    /// Returns the status code of this error.
+    pub fn code(self) -> i32 {
+        match self {
+            SCError::Other(code) => code,
+            _ => self.known().1,
+        }
+    }
+
+    /// Returns a description of this error. Errors without a `kSCStatus*` constant all have the
+    /// same description, see [`system_message`] for a more specific one.
+    ///
+    /// [`system_message`]: #method.system_message
+    pub fn message(self) -> &'static str {
+        match self {
+            SCError::Other(_) => "Unknown error",
+            _ => self.known().2,
+        }
+    }
+
+    /// Returns the error of the last failed SystemConfiguration call on this thread. Returns
+    /// [`SCError::Failed`] if the framework did not record a reason.
+    ///
+    /// See [`SCError()`] for details.
+    ///
+    /// [`SCError::Failed`]: #variant.Failed
+    /// [`SCError()`]: https://developer.apple.com/documentation/systemconfiguration/1516922-scerror?language=objc
+    pub fn last() -> Self {
+        let code = unsafe { crate::sys::system_configuration::SCError() };
+        Self::from_code(code).unwrap_or(SCError::Failed)
+    }
+
+    /// Returns the error of the last failed SystemConfiguration call on this thread, as a
+    /// `CFError` in the SystemConfiguration error domain.
+    ///
+    /// See [`SCCopyLastError`] for details.
+    ///
+    /// [`SCCopyLastError`]: https://developer.apple.com/documentation/systemconfiguration/1517073-sccopylasterror?language=objc
+    pub fn last_cf_error() -> CFError {
+        unsafe { CFError::wrap_under_create_rule(SCCopyLastError()) }
+    }
+
+    /// Returns the description of this error provided by the framework. Unlike [`message`], this
+    /// also describes `errno` and Mach error codes.
+    ///
+    /// See [`SCErrorString`] for details.
+    ///
+    /// [`message`]: #method.message
+    /// [`SCErrorString`]: https://developer.apple.com/documentation/systemconfiguration/1516916-scerrorstring?language=objc
+    pub fn system_message(self) -> String {
+        let message = unsafe { SCErrorString(self.code()) };
+        if message.is_null() {
+            self.message().to_owned()
+        } else {
+            unsafe { CStr::from_ptr(message) }
+                .to_string_lossy()
+                .into_owned()
+        }
+    }
+
+    /// Returns `Ok` if `success` is true, and otherwise the error of the failed call.
+    pub(crate) fn check(success: Boolean) -> Result<(), Self> {
+        if success != 0 {
+            Ok(())
+        } else {
+            Err(Self::last())
+        }
+    }
+
+    fn known(self) -> (SCError, i32, &'static str) {
+        *KNOWN_ERRORS
+            .iter()
+            .find(|(error, _, _)| *error == self)
+            .expect("All errors except Other are known")
+    }
+}
+
+impl fmt::Display for SCError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (status {})", self.message(), self.code())
+    }
+}
+
+impl Error for SCError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn code_round_trip() {
+        for (error, code, message) in KNOWN_ERRORS.iter() {
+            assert_eq!(SCError::from_code(*code), Some(*error));
+            assert_eq!(error.code(), *code);
+            assert_eq!(error.message(), *message);
+        }
+        assert_eq!(SCError::from_code(1005), Some(SCError::KeyExists));
+        assert_eq!(SCError::KeyExists.code(), 1005);
+    }
+
+    #[test]
+    fn unknown_codes() {
+        assert_eq!(SCError::from_code(0), None);
+        assert_eq!(SCError::from_code(13), Some(SCError::Other(13)));
+        assert_eq!(SCError::Other(13).code(), 13);
+        assert_eq!(SCError::Other(13).message(), "Unknown error");
+    }
+
+    #[test]
+    fn distinct_codes_and_messages() {
+        let codes = KNOWN_ERRORS
+            .iter()
+            .map(|known| known.1)
+            .collect::<HashSet<_>>();
+        let messages = KNOWN_ERRORS
+            .iter()
+            .map(|known| known.2)
+            .collect::<HashSet<_>>();
+        assert_eq!(codes.len(), KNOWN_ERRORS.len());
+        assert_eq!(messages.len(), KNOWN_ERRORS.len());
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(SCError::NoKey.to_string(), "No such key (status 1004)");
+        assert_eq!(SCError::Other(-1).to_string(), "Unknown error (status -1)");
+    }
+}
@@ -25,7 +25,12 @@ pub extern crate core_foundation;
 /// Low-level SystemConfiguration bindings
 pub extern crate system_configuration_sys as sys;
 
+pub mod authorization;
+pub mod dispatch;
 pub mod dynamic_store;
+pub mod error;
 pub mod network_configuration;
 pub mod network_reachability;
 pub mod preferences;
+pub mod property_list;
+pub mod proxies;
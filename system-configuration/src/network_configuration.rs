@@ -3,24 +3,51 @@
 //! [`SCNetworkConfiguration`]: https://developer.apple.com/documentation/systemconfiguration/scnetworkconfiguration?language=objc
 use core_foundation::{
     array::CFArray,
-    base::{TCFType, ToVoid},
+    base::{CFType, TCFType, ToVoid},
+    dictionary::CFDictionary,
     string::CFString,
 };
 use system_configuration_sys::network_configuration::{
-    SCNetworkInterfaceCopyAll, SCNetworkInterfaceGetBSDName, SCNetworkInterfaceGetInterfaceType,
-    SCNetworkInterfaceGetLocalizedDisplayName, SCNetworkInterfaceGetTypeID, SCNetworkInterfaceRef,
+    SCNetworkInterfaceCopyAll, SCNetworkInterfaceCopyMTU,
+    SCNetworkInterfaceForceConfigurationRefresh, SCNetworkInterfaceGetBSDName,
+    SCNetworkInterfaceGetConfiguration, SCNetworkInterfaceGetExtendedConfiguration,
+    SCNetworkInterfaceGetHardwareAddressString, SCNetworkInterfaceGetInterface,
+    SCNetworkInterfaceGetInterfaceType, SCNetworkInterfaceGetLocalizedDisplayName,
+    SCNetworkInterfaceGetSupportedInterfaceTypes, SCNetworkInterfaceGetSupportedProtocolTypes,
+    SCNetworkInterfaceGetTypeID, SCNetworkInterfaceRef, SCNetworkInterfaceSetMTU,
     SCNetworkServiceCopyAll, SCNetworkServiceGetEnabled, SCNetworkServiceGetInterface,
     SCNetworkServiceGetServiceID, SCNetworkServiceGetTypeID, SCNetworkServiceRef,
     SCNetworkSetCopyCurrent, SCNetworkSetGetServiceOrder, SCNetworkSetGetTypeID, SCNetworkSetRef,
 };
 
-use crate::preferences::SCPreferences;
+use crate::{error::SCError, preferences::SCPreferences};
+use std::{error::Error, fmt, os::raw::c_int};
+
+mod bond;
+mod media;
+mod vlan;
+
+pub use self::bond::{AggregationStatus, BondMemberStatus, BondStatus, SCBondInterface};
+pub use self::media::{Media, MediaOption, MediaOptions, MediaSubType, SetMediaError};
+pub use self::vlan::{InvalidVlanTagError, SCVLANInterface, VlanTag};
 
 core_foundation::declare_TCFType!(
     /// Represents a network interface.
     ///
     /// See [`SCNetworkInterfaceRef`] and its [methods] for details.
     ///
+    /// # Configuration changes
+    ///
+    /// Setters such as [`set_mtu`] and [`set_media`] do not change the interface directly. They
+    /// stage the change in the preferences the interface was read from, for example through
+    /// [`SCNetworkService::network_interface`], and the change takes effect once the preferences
+    /// are committed and applied. Interfaces returned by [`get_interfaces`] were not read from any
+    /// preferences, so the setters fail for them.
+    ///
+    /// [`set_mtu`]: #method.set_mtu
+    /// [`set_media`]: #method.set_media
+    /// [`SCNetworkService::network_interface`]: struct.SCNetworkService.html#method.network_interface
+    /// [`get_interfaces`]: fn.get_interfaces.html
     /// [`SCNetworkInterfaceRef`]: https://developer.apple.com/documentation/systemconfiguration/scnetworkinterfaceref?language=objc
     /// [methods]: https://developer.apple.com/documentation/systemconfiguration/scnetworkconfiguration?language=objc
     SCNetworkInterface,
@@ -32,7 +59,6 @@ core_foundation::impl_TCFType!(
     SCNetworkInterfaceGetTypeID
 );
 
-// TODO: implement all the other methods a SCNetworkInterface has
 impl SCNetworkInterface {
     /// Get type of the network interface, if the type is recognized, returns `None` otherwise.
     ///
@@ -90,6 +116,240 @@ impl SCNetworkInterface {
             }
         }
     }
+
+    /// Returns the hardware address of the interface as a string, such as `00:1b:63:84:45:e6`.
+    ///
+    /// See [`SCNetworkInterfaceGetHardwareAddressString`] for details.
+    ///
+    /// [`SCNetworkInterfaceGetHardwareAddressString`]: https://developer.apple.com/documentation/systemconfiguration/1516870-scnetworkinterfacegethardwareadd?language=objc
+    pub fn hardware_address_string(&self) -> Option<CFString> {
+        unsafe {
+            let ptr = SCNetworkInterfaceGetHardwareAddressString(self.0);
+            if ptr.is_null() {
+                None
+            } else {
+                Some(CFString::wrap_under_get_rule(ptr))
+            }
+        }
+    }
+
+    /// Returns the interface this interface is layered on top of, such as the Ethernet interface
+    /// under a PPPoE interface. Returns `None` for interfaces that are not layered.
+    ///
+    /// See [`SCNetworkInterfaceGetInterface`] for details.
+    ///
+    /// [`SCNetworkInterfaceGetInterface`]: https://developer.apple.com/documentation/systemconfiguration/1516824-scnetworkinterfacegetinterface?language=objc
+    pub fn underlying_interface(&self) -> Option<SCNetworkInterface> {
+        unsafe {
+            let ptr = SCNetworkInterfaceGetInterface(self.0);
+            if ptr.is_null() {
+                None
+            } else {
+                Some(SCNetworkInterface::wrap_under_get_rule(ptr))
+            }
+        }
+    }
+
+    /// Returns the types of interfaces that can be layered on top of this interface. Types that
+    /// are not recognized are left out.
+    ///
+    /// See [`SCNetworkInterfaceGetSupportedInterfaceTypes`] for details.
+    ///
+    /// [`SCNetworkInterfaceGetSupportedInterfaceTypes`]: https://developer.apple.com/documentation/systemconfiguration/1517076-scnetworkinterfacegetsupportedin?language=objc
+    pub fn supported_interface_types(&self) -> Vec<SCNetworkInterfaceType> {
+        let types = unsafe {
+            let array_ptr = SCNetworkInterfaceGetSupportedInterfaceTypes(self.0);
+            if array_ptr.is_null() {
+                return Vec::new();
+            }
+            CFArray::<CFString>::wrap_under_get_rule(array_ptr)
+        };
+        types
+            .iter()
+            .filter_map(|type_id| SCNetworkInterfaceType::from_cfstring(&type_id))
+            .collect()
+    }
+
+    /// Returns the types of protocols that can be configured on this interface.
+    ///
+    /// See [`SCNetworkInterfaceGetSupportedProtocolTypes`] for details.
+    ///
+    /// [`SCNetworkInterfaceGetSupportedProtocolTypes`]: https://developer.apple.com/documentation/systemconfiguration/1517288-scnetworkinterfacegetsupportedpr?language=objc
+    pub fn supported_protocol_types(&self) -> Vec<SCNetworkProtocolType> {
+        let types = unsafe {
+            let array_ptr = SCNetworkInterfaceGetSupportedProtocolTypes(self.0);
+            if array_ptr.is_null() {
+                return Vec::new();
+            }
+            CFArray::<CFString>::wrap_under_get_rule(array_ptr)
+        };
+        types
+            .iter()
+            .map(|type_id| SCNetworkProtocolType::from_cfstring(&type_id))
+            .collect()
+    }
+
+    /// Returns the configuration settings of the interface, or `None` if it has none.
+    ///
+    /// See [`SCNetworkInterfaceGetConfiguration`] for details.
+    ///
+    /// [`SCNetworkInterfaceGetConfiguration`]: https://developer.apple.com/documentation/systemconfiguration/1517083-scnetworkinterfacegetconfigurati?language=objc
+    pub fn configuration(&self) -> Option<CFDictionary<CFString, CFType>> {
+        unsafe {
+            let dict_ptr = SCNetworkInterfaceGetConfiguration(self.0);
+            if dict_ptr.is_null() {
+                None
+            } else {
+                Some(CFDictionary::wrap_under_get_rule(dict_ptr))
+            }
+        }
+    }
+
+    /// Returns the configuration settings of the given extended type, such as `EAPOL`, or `None`
+    /// if there are none.
+    ///
+    /// See [`SCNetworkInterfaceGetExtendedConfiguration`] for details.
+    ///
+    /// [`SCNetworkInterfaceGetExtendedConfiguration`]: https://developer.apple.com/documentation/systemconfiguration/1516831-scnetworkinterfacegetextendedcon?language=objc
+    pub fn extended_configuration(
+        &self,
+        extended_type: &CFString,
+    ) -> Option<CFDictionary<CFString, CFType>> {
+        unsafe {
+            let dict_ptr = SCNetworkInterfaceGetExtendedConfiguration(
+                self.0,
+                extended_type.as_concrete_TypeRef(),
+            );
+            if dict_ptr.is_null() {
+                None
+            } else {
+                Some(CFDictionary::wrap_under_get_rule(dict_ptr))
+            }
+        }
+    }
+
+    /// Asks the system to reconfigure the interface, for example to renew its DHCP lease.
+    ///
+    /// See [`SCNetworkInterfaceForceConfigurationRefresh`] for details.
+    ///
+    /// [`SCNetworkInterfaceForceConfigurationRefresh`]: https://developer.apple.com/documentation/systemconfiguration/1516879-scnetworkinterfaceforceconfigura?language=objc
+    pub fn force_configuration_refresh(&self) -> Result<(), SCError> {
+        SCError::check(unsafe { SCNetworkInterfaceForceConfigurationRefresh(self.0) })
+    }
+
+    /// Returns the current MTU of the interface, and the range of MTUs it supports.
+    ///
+    /// See [`SCNetworkInterfaceCopyMTU`] for details.
+    ///
+    /// [`SCNetworkInterfaceCopyMTU`]: https://developer.apple.com/documentation/systemconfiguration/1517075-scnetworkinterfacecopymtu?language=objc
+    pub fn mtu(&self) -> Result<Mtu, SCError> {
+        let mut current: c_int = -1;
+        let mut min: c_int = -1;
+        let mut max: c_int = -1;
+        SCError::check(unsafe {
+            SCNetworkInterfaceCopyMTU(self.0, &mut current, &mut min, &mut max)
+        })?;
+        Ok(Mtu {
+            current: u32::try_from(current).ok(),
+            min: u32::try_from(min).ok(),
+            max: u32::try_from(max).ok(),
+        })
+    }
+
+    /// Sets the MTU of the interface. Fails with [`SetMtuError::OutOfRange`] without changing the
+    /// MTU if `mtu` is outside the range returned by [`mtu`].
+    ///
+    /// See [configuration changes] for when the new MTU takes effect, and
+    /// [`SCNetworkInterfaceSetMTU`] for details.
+    ///
+    /// [`SetMtuError::OutOfRange`]: enum.SetMtuError.html#variant.OutOfRange
+    /// [`mtu`]: #method.mtu
+    /// [configuration changes]: struct.SCNetworkInterface.html#configuration-changes
+    /// [`SCNetworkInterfaceSetMTU`]: https://developer.apple.com/documentation/systemconfiguration/1516852-scnetworkinterfacesetmtu?language=objc
+    pub fn set_mtu(&self, mtu: u32) -> Result<(), SetMtuError> {
+        let range = self.mtu().map_err(SetMtuError::Interface)?;
+        let raw_mtu = match c_int::try_from(mtu) {
+            Ok(raw_mtu) if range.contains(mtu) => raw_mtu,
+            _ => {
+                return Err(SetMtuError::OutOfRange {
+                    mtu,
+                    min: range.min,
+                    max: range.max,
+                })
+            }
+        };
+        SCError::check(unsafe { SCNetworkInterfaceSetMTU(self.0, raw_mtu) })
+            .map_err(SetMtuError::Interface)
+    }
+}
+
+/// The MTU of a network interface, and the range of MTUs it supports.
+///
+/// Returned by [`SCNetworkInterface::mtu`].
+///
+/// [`SCNetworkInterface::mtu`]: struct.SCNetworkInterface.html#method.mtu
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Mtu {
+    /// The current MTU, or `None` if the interface does not report it.
+    pub current: Option<u32>,
+    /// The smallest supported MTU, or `None` if the interface does not report it.
+    pub min: Option<u32>,
+    /// The largest supported MTU, or `None` if the interface does not report it.
+    pub max: Option<u32>,
+}
+
+impl Mtu {
+    /// Returns true if `mtu` is within the supported range. Bounds that are not reported by the
+    /// interface do not limit the range.
+    pub fn contains(&self, mtu: u32) -> bool {
+        self.min.map_or(true, |min| mtu >= min) && self.max.map_or(true, |max| mtu <= max)
+    }
+}
+
+/// Failure to set the MTU of a network interface with [`SCNetworkInterface::set_mtu`].
+///
+/// [`SCNetworkInterface::set_mtu`]: struct.SCNetworkInterface.html#method.set_mtu
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SetMtuError {
+    /// The MTU is outside the range supported by the interface.
+    OutOfRange {
+        /// The rejected MTU.
+        mtu: u32,
+        /// The smallest supported MTU, if reported by the interface.
+        min: Option<u32>,
+        /// The largest supported MTU, if reported by the interface.
+        max: Option<u32>,
+    },
+    /// Reading the supported range or setting the MTU failed.
+    Interface(SCError),
+}
+
+impl fmt::Display for SetMtuError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::OutOfRange { mtu, min, max } => {
+                write!(f, "MTU {} is outside the supported range ", mtu)?;
+                match min {
+                    Some(min) => write!(f, "{}", min)?,
+                    None => write!(f, "?")?,
+                }
+                match max {
+                    Some(max) => write!(f, "-{}", max),
+                    None => write!(f, "-?"),
+                }
+            }
+            Self::Interface(error) => write!(f, "Failed to set MTU: {}", error),
+        }
+    }
+}
+
+impl Error for SetMtuError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::OutOfRange { .. } => None,
+            Self::Interface(error) => Some(error),
+        }
+    }
 }
 
 /// Represents the possible network interface types.
@@ -195,6 +455,61 @@ impl SCNetworkInterfaceType {
     }
 }
 
+/// Represents the types of protocols that can be configured on a network interface.
+///
+/// See [_Network Protocol Types_] documentation for details.
+///
+/// [_Network Protocol Types_]: https://developer.apple.com/documentation/systemconfiguration/scnetworkconfiguration/network_protocol_types?language=objc
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SCNetworkProtocolType {
+    /// AppleTalk protocol. No longer supported since macOS 10.15.
+    AppleTalk,
+    /// DNS protocol.
+    DNS,
+    /// IPv4 protocol.
+    IPv4,
+    /// IPv6 protocol.
+    IPv6,
+    /// Protocol proxies.
+    Proxies,
+    /// SMB protocol.
+    SMB,
+    /// A protocol type without a `kSCNetworkProtocolType*` constant, with its raw name.
+    Other(String),
+}
+
+impl SCNetworkProtocolType {
+    /// Constructs a type by matching it to the string constants used to identify a network
+    /// protocol type. Types that match no constant are returned as [`Other`].
+    ///
+    /// [`Other`]: #variant.Other
+    pub fn from_cfstring(type_id: &CFString) -> Self {
+        use system_configuration_sys::network_configuration::*;
+
+        let id_is_equal_to = |const_str| -> bool {
+            let const_str = unsafe { CFString::wrap_under_get_rule(const_str) };
+            &const_str == type_id
+        };
+        unsafe {
+            if id_is_equal_to(kSCNetworkProtocolTypeAppleTalk) {
+                SCNetworkProtocolType::AppleTalk
+            } else if id_is_equal_to(kSCNetworkProtocolTypeDNS) {
+                SCNetworkProtocolType::DNS
+            } else if id_is_equal_to(kSCNetworkProtocolTypeIPv4) {
+                SCNetworkProtocolType::IPv4
+            } else if id_is_equal_to(kSCNetworkProtocolTypeIPv6) {
+                SCNetworkProtocolType::IPv6
+            } else if id_is_equal_to(kSCNetworkProtocolTypeProxies) {
+                SCNetworkProtocolType::Proxies
+            } else if id_is_equal_to(kSCNetworkProtocolTypeSMB) {
+                SCNetworkProtocolType::SMB
+            } else {
+                SCNetworkProtocolType::Other(type_id.to_string())
+            }
+        }
+    }
+}
+
 /// Retrieve all current network interfaces
 ///
 /// See [`SCNetworkInterfaceCopyAll`] for more details.
@@ -223,13 +538,13 @@ core_foundation::impl_TCFType!(
 
 impl SCNetworkService {
     /// Returns an array of all network services
-    pub fn get_services(prefs: &SCPreferences) -> CFArray<Self> {
+    pub fn get_services(prefs: &SCPreferences) -> Result<CFArray<Self>, SCError> {
         unsafe {
             let array_ptr = SCNetworkServiceCopyAll(prefs.to_void());
             if array_ptr.is_null() {
-                return create_empty_array();
+                return Err(SCError::last());
             }
-            CFArray::<Self>::wrap_under_create_rule(array_ptr)
+            Ok(CFArray::<Self>::wrap_under_create_rule(array_ptr))
         }
     }
 
@@ -276,10 +591,16 @@ core_foundation::declare_TCFType!(
 core_foundation::impl_TCFType!(SCNetworkSet, SCNetworkSetRef, SCNetworkSetGetTypeID);
 
 impl SCNetworkSet {
-    /// Constructs a new set of network services from the preferences.
-    pub fn new(prefs: &SCPreferences) -> Self {
-        let ptr = unsafe { SCNetworkSetCopyCurrent(prefs.to_void()) };
-        unsafe { SCNetworkSet::wrap_under_create_rule(ptr) }
+    /// Constructs a new set of network services from the current set in the preferences.
+    pub fn new(prefs: &SCPreferences) -> Result<Self, SCError> {
+        unsafe {
+            let ptr = SCNetworkSetCopyCurrent(prefs.to_void());
+            if ptr.is_null() {
+                Err(SCError::last())
+            } else {
+                Ok(SCNetworkSet::wrap_under_create_rule(ptr))
+            }
+        }
     }
 
     /// Returns an list of network service identifiers, ordered by their priority.
@@ -329,11 +650,74 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_interface_accessors() {
+        for iface in get_interfaces().into_iter() {
+            let _ = iface.hardware_address_string();
+            let _ = iface.supported_interface_types();
+            let _ = iface.supported_protocol_types();
+            let _ = iface.configuration();
+            let _ = iface.extended_configuration(&CFString::new("EAPOL"));
+            if let Ok(mtu) = iface.mtu() {
+                if let Some(current) = mtu.current {
+                    assert!(mtu.contains(current));
+                }
+            }
+            if let Ok(media_options) = iface.media_options() {
+                if let Some(current) = media_options.current {
+                    let _ = iface.media_subtype_options(&current.subtype);
+                }
+            }
+
+            let mut depth = 0;
+            let mut current = iface.underlying_interface();
+            while let Some(underlying) = current {
+                depth += 1;
+                assert!(depth < 8, "Interface layering does not end");
+                current = underlying.underlying_interface();
+            }
+        }
+    }
+
+    #[test]
+    fn test_mtu_range() {
+        let mtu = Mtu {
+            current: Some(1500),
+            min: Some(1280),
+            max: Some(9000),
+        };
+        assert!(mtu.contains(1280));
+        assert!(mtu.contains(9000));
+        assert!(!mtu.contains(1279));
+        assert!(!mtu.contains(9001));
+
+        let unbounded = Mtu {
+            current: Some(1500),
+            min: None,
+            max: None,
+        };
+        assert!(unbounded.contains(0));
+        assert!(unbounded.contains(u32::MAX));
+    }
+
+    #[test]
+    fn test_set_mtu_error_display() {
+        let error = SetMtuError::OutOfRange {
+            mtu: 20,
+            min: Some(1280),
+            max: None,
+        };
+        assert_eq!(
+            error.to_string(),
+            "MTU 20 is outside the supported range 1280-?"
+        );
+    }
+
     #[test]
     fn test_service_order() {
-        let prefs = SCPreferences::default(&CFString::new("test"));
-        let services = SCNetworkService::get_services(&prefs);
-        let set = SCNetworkSet::new(&prefs);
+        let prefs = SCPreferences::default(&CFString::new("test")).unwrap();
+        let services = SCNetworkService::get_services(&prefs).unwrap();
+        let set = SCNetworkSet::new(&prefs).unwrap();
         let service_order = set.service_order();
 
         assert!(service_order.iter().all(|service_id| {
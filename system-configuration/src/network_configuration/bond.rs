@@ -0,0 +1,405 @@
+//! Ethernet bond (link aggregation) interfaces.
+
+use super::{create_empty_array, SCNetworkInterface, SCNetworkInterfaceType};
+use crate::{
+    error::SCError,
+    preferences::SCPreferences,
+    property_list::PropertyValue,
+    sys::network_configuration::{
+        kSCBondStatusDeviceAggregationStatus, kSCBondStatusDeviceCollecting,
+        kSCBondStatusDeviceDistributing, kSCBondStatusLinkInvalid, kSCBondStatusNoPartner,
+        kSCBondStatusNotInActiveGroup, kSCBondStatusOK, kSCBondStatusUnknown,
+        SCBondInterfaceCopyAll, SCBondInterfaceCopyAvailableMemberInterfaces,
+        SCBondInterfaceCopyStatus, SCBondInterfaceCreate, SCBondInterfaceGetMemberInterfaces,
+        SCBondInterfaceGetOptions, SCBondInterfaceRef, SCBondInterfaceRemove,
+        SCBondInterfaceSetLocalizedDisplayName, SCBondInterfaceSetMemberInterfaces,
+        SCBondInterfaceSetOptions, SCBondStatusGetInterfaceStatus, SCBondStatusGetMemberInterfaces,
+        SCBondStatusGetTypeID, SCBondStatusRef, SCNetworkInterfaceGetTypeID, SCNetworkInterfaceRef,
+    },
+};
+use core_foundation::{
+    array::CFArray,
+    base::{CFType, TCFType, ToVoid},
+    dictionary::{CFDictionary, CFDictionaryRef},
+    propertylist::CFPropertyList,
+    string::CFString,
+};
+use std::{fmt, ptr};
+
+core_foundation::declare_TCFType!(
+    /// An Ethernet bond interface, which aggregates several Ethernet interfaces into one link.
+    ///
+    /// Bonds are network interfaces with the same Core Foundation type, so `downcast` from a
+    /// `CFType` succeeds for any network interface. Use [`from_interface`] to check that an
+    /// interface is a bond.
+    ///
+    /// See [`SCBondInterfaceRef`] for details.
+    ///
+    /// [`from_interface`]: #method.from_interface
+    /// [`SCBondInterfaceRef`]: https://developer.apple.com/documentation/systemconfiguration/scbondinterfaceref?language=objc
+    SCBondInterface,
+    SCBondInterfaceRef
+);
+core_foundation::impl_TCFType!(
+    SCBondInterface,
+    SCBondInterfaceRef,
+    SCNetworkInterfaceGetTypeID
+);
+
+impl SCBondInterface {
+    /// Returns all bond interfaces in the preferences.
+    ///
+    /// See [`SCBondInterfaceCopyAll`] for details.
+    ///
+    /// [`SCBondInterfaceCopyAll`]: https://developer.apple.com/documentation/systemconfiguration/1517337-scbondinterfacecopyall?language=objc
+    pub fn get_all(prefs: &SCPreferences) -> Result<CFArray<Self>, SCError> {
+        unsafe {
+            let array_ptr = SCBondInterfaceCopyAll(prefs.to_void());
+            if array_ptr.is_null() {
+                return Err(SCError::last());
+            }
+            Ok(CFArray::<Self>::wrap_under_create_rule(array_ptr))
+        }
+    }
+
+    /// Returns the interfaces that can be added to a bond, that is, Ethernet interfaces that are
+    /// not already members of a bond or otherwise in use.
+    ///
+    /// See [`SCBondInterfaceCopyAvailableMemberInterfaces`] for details.
+    ///
+    /// [`SCBondInterfaceCopyAvailableMemberInterfaces`]: https://developer.apple.com/documentation/systemconfiguration/1517309-scbondinterfacecopyavailablememb?language=objc
+    pub fn available_member_interfaces(
+        prefs: &SCPreferences,
+    ) -> Result<CFArray<SCNetworkInterface>, SCError> {
+        unsafe {
+            let array_ptr = SCBondInterfaceCopyAvailableMemberInterfaces(prefs.to_void());
+            if array_ptr.is_null() {
+                return Err(SCError::last());
+            }
+            Ok(CFArray::<SCNetworkInterface>::wrap_under_create_rule(
+                array_ptr,
+            ))
+        }
+    }
+
+    /// Creates a new bond interface without members in the preferences.
+    ///
+    /// See [`SCBondInterfaceCreate`] for details.
+    ///
+    /// [`SCBondInterfaceCreate`]: https://developer.apple.com/documentation/systemconfiguration/1516888-scbondinterfacecreate?language=objc
+    pub fn create(prefs: &SCPreferences) -> Result<Self, SCError> {
+        unsafe {
+            let bond_ref = SCBondInterfaceCreate(prefs.to_void());
+            if bond_ref.is_null() {
+                Err(SCError::last())
+            } else {
+                Ok(Self::wrap_under_create_rule(bond_ref))
+            }
+        }
+    }
+
+    /// Removes the bond interface from the preferences it was read from.
+    ///
+    /// See [`SCBondInterfaceRemove`] for details.
+    ///
+    /// [`SCBondInterfaceRemove`]: https://developer.apple.com/documentation/systemconfiguration/1517186-scbondinterfaceremove?language=objc
+    pub fn remove(self) -> Result<(), SCError> {
+        SCError::check(unsafe { SCBondInterfaceRemove(self.0) })
+    }
+
+    /// Returns the interface as a bond, or `None` if it is not a bond interface.
+    pub fn from_interface(interface: &SCNetworkInterface) -> Option<Self> {
+        match interface.interface_type()? {
+            SCNetworkInterfaceType::Bond => unsafe {
+                Some(Self::wrap_under_get_rule(interface.as_concrete_TypeRef()))
+            },
+            _ => None,
+        }
+    }
+
+    /// Returns the bond as a generic network interface.
+    pub fn as_network_interface(&self) -> SCNetworkInterface {
+        unsafe { SCNetworkInterface::wrap_under_get_rule(self.0) }
+    }
+
+    /// Returns the member interfaces of the bond.
+    ///
+    /// See [`SCBondInterfaceGetMemberInterfaces`] for details.
+    ///
+    /// [`SCBondInterfaceGetMemberInterfaces`]: https://developer.apple.com/documentation/systemconfiguration/1516835-scbondinterfacegetmemberinterfac?language=objc
+    pub fn member_interfaces(&self) -> CFArray<SCNetworkInterface> {
+        unsafe {
+            let array_ptr = SCBondInterfaceGetMemberInterfaces(self.0);
+            if array_ptr.is_null() {
+                return create_empty_array();
+            }
+            CFArray::<SCNetworkInterface>::wrap_under_get_rule(array_ptr)
+        }
+    }
+
+    /// Replaces the member interfaces of the bond.
+    ///
+    /// See [`SCBondInterfaceSetMemberInterfaces`] for details.
+    ///
+    /// [`SCBondInterfaceSetMemberInterfaces`]: https://developer.apple.com/documentation/systemconfiguration/1517326-scbondinterfacesetmemberinterfac?language=objc
+    pub fn set_member_interfaces(&self, members: &[SCNetworkInterface]) -> Result<(), SCError> {
+        let members = CFArray::from_CFTypes(members);
+        SCError::check(unsafe {
+            SCBondInterfaceSetMemberInterfaces(self.0, members.as_concrete_TypeRef())
+        })
+    }
+
+    /// Returns the configuration options of the bond, such as its mode, or `None` if it has none.
+    ///
+    /// See [`SCBondInterfaceGetOptions`] for details.
+    ///
+    /// [`SCBondInterfaceGetOptions`]: https://developer.apple.com/documentation/systemconfiguration/1517104-scbondinterfacegetoptions?language=objc
+    pub fn options(&self) -> Option<CFDictionary<CFString, CFType>> {
+        unsafe {
+            let dict_ptr = SCBondInterfaceGetOptions(self.0);
+            if dict_ptr.is_null() {
+                None
+            } else {
+                Some(CFDictionary::wrap_under_get_rule(dict_ptr))
+            }
+        }
+    }
+
+    /// Replaces the configuration options of the bond.
+    ///
+    /// See [`SCBondInterfaceSetOptions`] for details.
+    ///
+    /// [`SCBondInterfaceSetOptions`]: https://developer.apple.com/documentation/systemconfiguration/1516837-scbondinterfacesetoptions?language=objc
+    pub fn set_options(&self, options: &CFDictionary<CFString, CFType>) -> Result<(), SCError> {
+        SCError::check(unsafe { SCBondInterfaceSetOptions(self.0, options.as_concrete_TypeRef()) })
+    }
+
+    /// Sets the localized display name of the bond.
+    ///
+    /// See [`SCBondInterfaceSetLocalizedDisplayName`] for details.
+    ///
+    /// [`SCBondInterfaceSetLocalizedDisplayName`]: https://developer.apple.com/documentation/systemconfiguration/1517290-scbondinterfacesetlocalizeddispl?language=objc
+    pub fn set_display_name(&self, name: &CFString) -> Result<(), SCError> {
+        SCError::check(unsafe {
+            SCBondInterfaceSetLocalizedDisplayName(self.0, name.as_concrete_TypeRef())
+        })
+    }
+
+    /// Returns the current link aggregation status of the bond and each of its members.
+    ///
+    /// See [`SCBondInterfaceCopyStatus`] for details.
+    ///
+    /// [`SCBondInterfaceCopyStatus`]: https://developer.apple.com/documentation/systemconfiguration/1516944-scbondinterfacecopystatus?language=objc
+    pub fn status(&self) -> Result<BondStatus, SCError> {
+        let status = unsafe {
+            let status_ref = SCBondInterfaceCopyStatus(self.0);
+            if status_ref.is_null() {
+                return Err(SCError::last());
+            }
+            SCBondStatus::wrap_under_create_rule(status_ref)
+        };
+
+        let members = unsafe {
+            let array_ptr = SCBondStatusGetMemberInterfaces(status.0);
+            if array_ptr.is_null() {
+                create_empty_array()
+            } else {
+                CFArray::<SCNetworkInterface>::wrap_under_get_rule(array_ptr)
+            }
+        };
+        let members = members
+            .iter()
+            .map(|interface| {
+                let report = status.interface_report(interface.as_concrete_TypeRef());
+                BondMemberStatus {
+                    interface: interface.clone(),
+                    aggregation: report.aggregation,
+                    collecting: report.collecting,
+                    distributing: report.distributing,
+                }
+            })
+            .collect();
+
+        Ok(BondStatus {
+            aggregation: status.interface_report(ptr::null()).aggregation,
+            members,
+        })
+    }
+}
+
+/// The link aggregation status of a bond or a bond member.
+///
+/// See [`SCBondStatusRef`] for details.
+///
+/// [`SCBondStatusRef`]: https://developer.apple.com/documentation/systemconfiguration/scbondstatusref?language=objc
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AggregationStatus {
+    /// `kSCBondStatusOK`: Enabled, active, running, and so on.
+    Ok,
+    /// `kSCBondStatusLinkInvalid`: The link state was not valid, for example down or half-duplex.
+    LinkInvalid,
+    /// `kSCBondStatusNoPartner`: The link has no partner.
+    NoPartner,
+    /// `kSCBondStatusNotInActiveGroup`: The link is not part of the active aggregation group.
+    NotInActiveGroup,
+    /// `kSCBondStatusUnknown`: The status could not be determined.
+    Unknown,
+    /// A status code without a `kSCBondStatus*` constant.
+    Other(u32),
+}
+
+impl AggregationStatus {
+    /// Returns the status for a `kSCBondStatus*` code.
+    pub fn from_code(code: u32) -> Self {
+        KNOWN_AGGREGATION_STATUSES
+            .iter()
+            .find(|(known_code, _)| *known_code == code)
+            .map_or(AggregationStatus::Other(code), |(_, status)| *status)
+    }
+}
+
+/// The known aggregation statuses, with their status codes.
+const KNOWN_AGGREGATION_STATUSES: [(u32, AggregationStatus); 5] = [
+    (kSCBondStatusOK, AggregationStatus::Ok),
+    (kSCBondStatusLinkInvalid, AggregationStatus::LinkInvalid),
+    (kSCBondStatusNoPartner, AggregationStatus::NoPartner),
+    (
+        kSCBondStatusNotInActiveGroup,
+        AggregationStatus::NotInActiveGroup,
+    ),
+    (kSCBondStatusUnknown, AggregationStatus::Unknown),
+];
+
+/// The status of a bond and its members.
+///
+/// Returned by [`SCBondInterface::status`].
+///
+/// [`SCBondInterface::status`]: struct.SCBondInterface.html#method.status
+#[derive(Debug, Clone, PartialEq)]
+pub struct BondStatus {
+    /// The aggregation status of the bond as a whole.
+    pub aggregation: AggregationStatus,
+    /// The status of each member interface.
+    pub members: Vec<BondMemberStatus>,
+}
+
+/// The status of one member interface of a bond.
+#[derive(Clone, PartialEq)]
+pub struct BondMemberStatus {
+    /// The member interface.
+    pub interface: SCNetworkInterface,
+    /// The aggregation status of the member.
+    pub aggregation: AggregationStatus,
+    /// True if the member is collecting, that is, receiving frames for the bond.
+    pub collecting: bool,
+    /// True if the member is distributing, that is, sending frames for the bond.
+    pub distributing: bool,
+}
+
+impl fmt::Debug for BondMemberStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BondMemberStatus")
+            .field("interface", &self.interface.bsd_name())
+            .field("aggregation", &self.aggregation)
+            .field("collecting", &self.collecting)
+            .field("distributing", &self.distributing)
+            .finish()
+    }
+}
+
+core_foundation::declare_TCFType!(
+    /// The raw status of a bond, converted into a [`BondStatus`].
+    ///
+    /// [`BondStatus`]: struct.BondStatus.html
+    SCBondStatus,
+    SCBondStatusRef
+);
+core_foundation::impl_TCFType!(SCBondStatus, SCBondStatusRef, SCBondStatusGetTypeID);
+
+/// The entries of one status dictionary of an `SCBondStatus`.
+struct InterfaceReport {
+    aggregation: AggregationStatus,
+    collecting: bool,
+    distributing: bool,
+}
+
+impl SCBondStatus {
+    /// Reads the status of a member interface, or of the bond as a whole if `interface` is NULL.
+    fn interface_report(&self, interface: SCNetworkInterfaceRef) -> InterfaceReport {
+        let dict_ref: CFDictionaryRef =
+            unsafe { SCBondStatusGetInterfaceStatus(self.0, interface) };
+        let dict = if dict_ref.is_null() {
+            None
+        } else {
+            let plist = unsafe { CFPropertyList::wrap_under_get_rule(dict_ref.cast()) };
+            PropertyValue::from_property_list(&plist)
+        };
+        let entry = |key| {
+            let key = unsafe { CFString::wrap_under_get_rule(key) }.to_string();
+            dict.as_ref()
+                .and_then(PropertyValue::as_dictionary)
+                .and_then(|dict| dict.get(&key).cloned())
+        };
+
+        InterfaceReport {
+            aggregation: unsafe { entry(kSCBondStatusDeviceAggregationStatus) }
+                .and_then(|status| status.as_i64())
+                .and_then(|code| u32::try_from(code).ok())
+                .map_or(AggregationStatus::Unknown, AggregationStatus::from_code),
+            collecting: unsafe { entry(kSCBondStatusDeviceCollecting) }
+                .and_then(|collecting| collecting.as_bool())
+                .unwrap_or(false),
+            distributing: unsafe { entry(kSCBondStatusDeviceDistributing) }
+                .and_then(|distributing| distributing.as_bool())
+                .unwrap_or(false),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aggregation_status_from_code() {
+        assert_eq!(AggregationStatus::from_code(0), AggregationStatus::Ok);
+        assert_eq!(
+            AggregationStatus::from_code(3),
+            AggregationStatus::NotInActiveGroup
+        );
+        assert_eq!(
+            AggregationStatus::from_code(999),
+            AggregationStatus::Unknown
+        );
+        assert_eq!(AggregationStatus::from_code(4), AggregationStatus::Other(4));
+    }
+
+    #[test]
+    fn from_interface_checks_type() {
+        for interface in super::super::get_interfaces().iter() {
+            let is_bond = matches!(
+                interface.interface_type(),
+                Some(SCNetworkInterfaceType::Bond)
+            );
+            assert_eq!(
+                SCBondInterface::from_interface(&interface).is_some(),
+                is_bond
+            );
+        }
+    }
+
+    #[test]
+    fn bond_status() {
+        let prefs = SCPreferences::default(&CFString::new("test")).unwrap();
+        for bond in SCBondInterface::get_all(&prefs).unwrap().iter() {
+            assert!(SCBondInterface::from_interface(&bond.as_network_interface()).is_some());
+            let members = bond.member_interfaces();
+            if let Ok(status) = bond.status() {
+                assert!(status
+                    .members
+                    .iter()
+                    .all(|member| members.iter().any(|m| *m == member.interface)));
+            }
+        }
+    }
+}
@@ -0,0 +1,471 @@
+//! Typed Ethernet media settings, such as `1000baseT` with `full-duplex`.
+
+use super::SCNetworkInterface;
+use crate::{
+    error::SCError,
+    property_list::PropertyValue,
+    sys::{
+        network_configuration::{
+            SCNetworkInterfaceCopyMediaOptions, SCNetworkInterfaceCopyMediaSubTypeOptions,
+            SCNetworkInterfaceCopyMediaSubTypes, SCNetworkInterfaceSetMediaOptions,
+        },
+        schema_definitions::{kSCPropNetEthernetMediaOptions, kSCPropNetEthernetMediaSubType},
+    },
+};
+use core_foundation::{
+    array::{CFArray, CFArrayRef},
+    base::TCFType,
+    dictionary::CFDictionaryRef,
+    propertylist::CFPropertyList,
+    string::CFString,
+};
+use std::{collections::HashSet, error::Error, fmt, ptr};
+
+/// Declares a media enum with known values and their names, plus an `Other` fallback that keeps
+/// the raw name of values that are not known.
+macro_rules! media_enum {
+    (
+        $(#[$enum_meta:meta])*
+        pub enum $name:ident {
+            $($(#[$variant_meta:meta])* $variant:ident => $value:expr,)*
+        }
+    ) => {
+        $(#[$enum_meta])*
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        pub enum $name {
+            $($(#[$variant_meta])* $variant,)*
+            /// A value that is not known to this crate, with its raw name.
+            Other(String),
+        }
+
+        impl $name {
+            /// Returns the name used for this value by the framework.
+            pub fn as_str(&self) -> &str {
+                match self {
+                    $($name::$variant => $value,)*
+                    $name::Other(name) => name,
+                }
+            }
+        }
+
+        impl From<&str> for $name {
+            fn from(name: &str) -> Self {
+                match name {
+                    $($value => $name::$variant,)*
+                    name => $name::Other(name.to_owned()),
+                }
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+    };
+}
+
+media_enum! {
+    /// The media subtype of an Ethernet interface, that is, its link type and speed.
+    pub enum MediaSubType {
+        /// Negotiate the media type with the link partner.
+        Autoselect => "autoselect",
+        /// No media, named `none` by the framework.
+        NoMedia => "none",
+        /// 10 Mbit/s over twisted pair.
+        TenBaseT => "10baseT/UTP",
+        /// 100 Mbit/s over twisted pair.
+        HundredBaseTX => "100baseTX",
+        /// 1 Gbit/s over twisted pair.
+        ThousandBaseT => "1000baseT",
+        /// 1 Gbit/s over multi-mode fiber.
+        ThousandBaseSX => "1000baseSX",
+        /// 1 Gbit/s over single-mode fiber.
+        ThousandBaseLX => "1000baseLX",
+        /// 2.5 Gbit/s over twisted pair.
+        TwoPointFiveGBaseT => "2500Base-T",
+        /// 5 Gbit/s over twisted pair.
+        FiveGBaseT => "5000Base-T",
+        /// 10 Gbit/s over twisted pair.
+        TenGBaseT => "10GbaseT",
+        /// 10 Gbit/s over multi-mode fiber.
+        TenGBaseSR => "10GbaseSR",
+        /// 10 Gbit/s over single-mode fiber.
+        TenGBaseLR => "10GbaseLR",
+    }
+}
+
+media_enum! {
+    /// An option of an Ethernet media subtype.
+    pub enum MediaOption {
+        /// Full-duplex operation.
+        FullDuplex => "full-duplex",
+        /// Half-duplex operation.
+        HalfDuplex => "half-duplex",
+        /// Ethernet flow control.
+        FlowControl => "flow-control",
+        /// Loopback in the hardware.
+        HardwareLoopback => "hw-loopback",
+        /// Energy Efficient Ethernet.
+        EnergyEfficientEthernet => "energy-efficient-ethernet",
+    }
+}
+
+/// A media setting of an Ethernet interface: a subtype and its options.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Media {
+    /// The media subtype.
+    pub subtype: MediaSubType,
+    /// The options of the subtype. The order is not significant.
+    pub options: Vec<MediaOption>,
+}
+
+impl Media {
+    /// Creates a media setting from a subtype and its options.
+    pub fn new(subtype: MediaSubType, options: Vec<MediaOption>) -> Self {
+        Media { subtype, options }
+    }
+
+    /// Returns the setting that negotiates the media type with the link partner.
+    pub fn autoselect() -> Self {
+        Media::new(MediaSubType::Autoselect, Vec::new())
+    }
+
+    /// Returns true if this setting negotiates the media type with the link partner.
+    pub fn is_autoselect(&self) -> bool {
+        self.subtype == MediaSubType::Autoselect
+    }
+
+    /// Returns true if both settings have the same subtype and the same set of options, in any
+    /// order. Options repeated in either setting count once.
+    pub fn matches(&self, other: &Media) -> bool {
+        self.subtype == other.subtype
+            && self.options.iter().collect::<HashSet<_>>()
+                == other.options.iter().collect::<HashSet<_>>()
+    }
+
+    fn from_dictionary_ref(dict_ref: CFDictionaryRef) -> Option<Self> {
+        if dict_ref.is_null() {
+            return None;
+        }
+        let plist = unsafe { CFPropertyList::wrap_under_create_rule(dict_ref.cast()) };
+        let value = PropertyValue::from_property_list(&plist)?;
+        Self::from_property_value(&value)
+    }
+
+    fn from_property_value(value: &PropertyValue) -> Option<Self> {
+        let dict = value.as_dictionary()?;
+        let subtype_key = unsafe { CFString::wrap_under_get_rule(kSCPropNetEthernetMediaSubType) };
+        let options_key = unsafe { CFString::wrap_under_get_rule(kSCPropNetEthernetMediaOptions) };
+        let subtype = dict.get(&subtype_key.to_string())?.as_str()?;
+        let options = dict
+            .get(&options_key.to_string())
+            .and_then(PropertyValue::as_array)
+            .map(options_from_values)
+            .unwrap_or_default();
+        Some(Media::new(MediaSubType::from(subtype), options))
+    }
+}
+
+impl fmt::Display for Media {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.subtype)?;
+        if !self.options.is_empty() {
+            let options = self
+                .options
+                .iter()
+                .map(MediaOption::as_str)
+                .collect::<Vec<_>>();
+            write!(f, " <{}>", options.join(","))?;
+        }
+        Ok(())
+    }
+}
+
+/// The media settings of an Ethernet interface.
+///
+/// Returned by [`SCNetworkInterface::media_options`].
+///
+/// [`SCNetworkInterface::media_options`]: struct.SCNetworkInterface.html#method.media_options
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MediaOptions {
+    /// The configured setting, or `None` if the interface has no media settings.
+    pub current: Option<Media>,
+    /// The setting currently in use, for example the negotiated one when autoselecting.
+    pub active: Option<Media>,
+    /// The settings the interface supports.
+    pub available: Vec<Media>,
+}
+
+impl MediaOptions {
+    /// Returns true if `media` is one of the available settings.
+    pub fn supports(&self, media: &Media) -> bool {
+        self.available
+            .iter()
+            .any(|available| available.matches(media))
+    }
+}
+
+/// Failure to set the media of a network interface with [`SCNetworkInterface::set_media`].
+///
+/// [`SCNetworkInterface::set_media`]: struct.SCNetworkInterface.html#method.set_media
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SetMediaError {
+    /// The interface does not report the requested setting as available.
+    Unsupported(Media),
+    /// Reading the available settings or setting the media failed.
+    Interface(SCError),
+}
+
+impl fmt::Display for SetMediaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unsupported(media) => write!(f, "Media {} is not supported", media),
+            Self::Interface(error) => write!(f, "Failed to set media: {}", error),
+        }
+    }
+}
+
+impl Error for SetMediaError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Unsupported(_) => None,
+            Self::Interface(error) => Some(error),
+        }
+    }
+}
+
+impl SCNetworkInterface {
+    /// Returns the current, active and available media settings of the interface. Available
+    /// settings that are not useful to select are left out.
+    ///
+    /// See [`SCNetworkInterfaceCopyMediaOptions`] for details.
+    ///
+    /// [`SCNetworkInterfaceCopyMediaOptions`]: https://developer.apple.com/documentation/systemconfiguration/1517355-scnetworkinterfacecopymediaoptio?language=objc
+    pub fn media_options(&self) -> Result<MediaOptions, SCError> {
+        let mut current = ptr::null();
+        let mut active = ptr::null();
+        let available = self.copy_media_options(&mut current, &mut active)?;
+        Ok(MediaOptions {
+            current: Media::from_dictionary_ref(current),
+            active: Media::from_dictionary_ref(active),
+            available: available
+                .map(|available| {
+                    let plist =
+                        unsafe { CFPropertyList::wrap_under_get_rule(available.as_CFTypeRef()) };
+                    match PropertyValue::from_property_list(&plist) {
+                        Some(PropertyValue::Array(values)) => values
+                            .iter()
+                            .filter_map(Media::from_property_value)
+                            .collect(),
+                        _ => Vec::new(),
+                    }
+                })
+                .unwrap_or_default(),
+        })
+    }
+
+    /// Returns the media subtypes the interface supports.
+    ///
+    /// See [`SCNetworkInterfaceCopyMediaSubTypes`] for details.
+    ///
+    /// [`SCNetworkInterfaceCopyMediaSubTypes`]: https://developer.apple.com/documentation/systemconfiguration/1517021-scnetworkinterfacecopymediasubty?language=objc
+    pub fn media_subtypes(&self) -> Result<Vec<MediaSubType>, SCError> {
+        let available = match self.copy_media_options(ptr::null_mut(), ptr::null_mut())? {
+            Some(available) => available,
+            None => return Ok(Vec::new()),
+        };
+        let subtypes = unsafe {
+            let array_ref = SCNetworkInterfaceCopyMediaSubTypes(available.as_concrete_TypeRef());
+            if array_ref.is_null() {
+                return Ok(Vec::new());
+            }
+            CFArray::<CFString>::wrap_under_create_rule(array_ref)
+        };
+        Ok(subtypes
+            .iter()
+            .map(|subtype| MediaSubType::from(subtype.to_string().as_str()))
+            .collect())
+    }
+
+    /// Returns the combinations of options the interface supports for the given subtype.
+    ///
+    /// See [`SCNetworkInterfaceCopyMediaSubTypeOptions`] for details.
+    ///
+    /// [`SCNetworkInterfaceCopyMediaSubTypeOptions`]: https://developer.apple.com/documentation/systemconfiguration/1516914-scnetworkinterfacecopymediasubty?language=objc
+    pub fn media_subtype_options(
+        &self,
+        subtype: &MediaSubType,
+    ) -> Result<Vec<Vec<MediaOption>>, SCError> {
+        let available = match self.copy_media_options(ptr::null_mut(), ptr::null_mut())? {
+            Some(available) => available,
+            None => return Ok(Vec::new()),
+        };
+        let subtype = CFString::new(subtype.as_str());
+        let option_sets = unsafe {
+            let array_ref = SCNetworkInterfaceCopyMediaSubTypeOptions(
+                available.as_concrete_TypeRef(),
+                subtype.as_concrete_TypeRef(),
+            );
+            if array_ref.is_null() {
+                return Ok(Vec::new());
+            }
+            CFArray::<CFArray<CFString>>::wrap_under_create_rule(array_ref)
+        };
+        Ok(option_sets
+            .iter()
+            .map(|options| {
+                options
+                    .iter()
+                    .map(|option| MediaOption::from(option.to_string().as_str()))
+                    .collect()
+            })
+            .collect())
+    }
+
+    /// Sets the media of the interface. Fails with [`SetMediaError::Unsupported`] without
+    /// changing the media if `media` is not one of the available settings returned by
+    /// [`media_options`]. Repeated options are only passed to the framework once.
+    ///
+    /// See [configuration changes] for when the new media takes effect, and
+    /// [`SCNetworkInterfaceSetMediaOptions`] for details.
+    ///
+    /// [`SetMediaError::Unsupported`]: enum.SetMediaError.html#variant.Unsupported
+    /// [`media_options`]: #method.media_options
+    /// [configuration changes]: struct.SCNetworkInterface.html#configuration-changes
+    /// [`SCNetworkInterfaceSetMediaOptions`]: https://developer.apple.com/documentation/systemconfiguration/1517286-scnetworkinterfacesetmediaoption?language=objc
+    pub fn set_media(&self, media: &Media) -> Result<(), SetMediaError> {
+        let media_options = self.media_options().map_err(SetMediaError::Interface)?;
+        if !media_options.supports(media) {
+            return Err(SetMediaError::Unsupported(media.clone()));
+        }
+
+        let subtype = CFString::new(media.subtype.as_str());
+        let mut seen = HashSet::new();
+        let options = media
+            .options
+            .iter()
+            .filter(|option| seen.insert(*option))
+            .map(|option| CFString::new(option.as_str()))
+            .collect::<Vec<_>>();
+        let options = CFArray::from_CFTypes(&options);
+        SCError::check(unsafe {
+            SCNetworkInterfaceSetMediaOptions(
+                self.as_concrete_TypeRef(),
+                subtype.as_concrete_TypeRef(),
+                options.as_concrete_TypeRef(),
+            )
+        })
+        .map_err(SetMediaError::Interface)
+    }
+
+    /// Calls `SCNetworkInterfaceCopyMediaOptions`, and returns the available media. The current
+    /// and active media are returned through `current` and `active` unless they are NULL, and
+    /// must then be released by the caller.
+    fn copy_media_options(
+        &self,
+        current: *mut CFDictionaryRef,
+        active: *mut CFDictionaryRef,
+    ) -> Result<Option<CFArray>, SCError> {
+        let mut available: CFArrayRef = ptr::null();
+        SCError::check(unsafe {
+            SCNetworkInterfaceCopyMediaOptions(
+                self.as_concrete_TypeRef(),
+                current,
+                active,
+                &mut available,
+                1,
+            )
+        })?;
+        if available.is_null() {
+            Ok(None)
+        } else {
+            Ok(Some(unsafe { CFArray::wrap_under_create_rule(available) }))
+        }
+    }
+}
+
+fn options_from_values(values: &[PropertyValue]) -> Vec<MediaOption> {
+    values
+        .iter()
+        .filter_map(PropertyValue::as_str)
+        .map(MediaOption::from)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip_names() {
+        for subtype in [
+            MediaSubType::Autoselect,
+            MediaSubType::NoMedia,
+            MediaSubType::ThousandBaseT,
+            MediaSubType::TwoPointFiveGBaseT,
+            MediaSubType::TenGBaseT,
+        ] {
+            assert_eq!(MediaSubType::from(subtype.as_str()), subtype);
+        }
+        assert_eq!(MediaOption::from("full-duplex"), MediaOption::FullDuplex);
+        assert_eq!(
+            MediaSubType::from("40GbaseSR4"),
+            MediaSubType::Other("40GbaseSR4".to_owned())
+        );
+        assert_eq!(MediaOption::from("flag0").as_str(), "flag0");
+    }
+
+    #[test]
+    fn supports_options_in_any_order() {
+        let media_options = MediaOptions {
+            current: Some(Media::autoselect()),
+            active: None,
+            available: vec![
+                Media::autoselect(),
+                Media::new(
+                    MediaSubType::ThousandBaseT,
+                    vec![MediaOption::FullDuplex, MediaOption::FlowControl],
+                ),
+            ],
+        };
+        assert!(media_options.current.as_ref().unwrap().is_autoselect());
+        assert!(media_options.supports(&Media::new(
+            MediaSubType::ThousandBaseT,
+            vec![MediaOption::FlowControl, MediaOption::FullDuplex],
+        )));
+        assert!(!media_options.supports(&Media::new(
+            MediaSubType::ThousandBaseT,
+            vec![MediaOption::FullDuplex],
+        )));
+        assert!(!media_options.supports(&Media::new(MediaSubType::HundredBaseTX, vec![])));
+    }
+
+    #[test]
+    fn match_duplicate_options() {
+        let full_duplex = Media::new(
+            MediaSubType::ThousandBaseT,
+            vec![MediaOption::FullDuplex, MediaOption::FlowControl],
+        );
+        let repeated = Media::new(
+            MediaSubType::ThousandBaseT,
+            vec![MediaOption::FullDuplex, MediaOption::FullDuplex],
+        );
+        assert!(!repeated.matches(&full_duplex));
+        assert!(!full_duplex.matches(&repeated));
+
+        let single = Media::new(MediaSubType::ThousandBaseT, vec![MediaOption::FullDuplex]);
+        assert!(repeated.matches(&single));
+        assert!(single.matches(&repeated));
+    }
+
+    #[test]
+    fn display() {
+        let media = Media::new(
+            MediaSubType::ThousandBaseT,
+            vec![MediaOption::FullDuplex, MediaOption::FlowControl],
+        );
+        assert_eq!(media.to_string(), "1000baseT <full-duplex,flow-control>");
+        assert_eq!(Media::autoselect().to_string(), "autoselect");
+    }
+}
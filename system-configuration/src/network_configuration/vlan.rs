@@ -0,0 +1,329 @@
+//! Virtual LAN (IEEE 802.1Q) interfaces.
+
+use super::{SCNetworkInterface, SCNetworkInterfaceType};
+use crate::{
+    error::SCError,
+    preferences::SCPreferences,
+    sys::network_configuration::{
+        SCNetworkInterfaceGetTypeID, SCVLANInterfaceCopyAll,
+        SCVLANInterfaceCopyAvailablePhysicalInterfaces, SCVLANInterfaceCreate,
+        SCVLANInterfaceGetOptions, SCVLANInterfaceGetPhysicalInterface, SCVLANInterfaceGetTag,
+        SCVLANInterfaceRef, SCVLANInterfaceRemove, SCVLANInterfaceSetLocalizedDisplayName,
+        SCVLANInterfaceSetOptions, SCVLANInterfaceSetPhysicalInterfaceAndTag,
+    },
+};
+use core_foundation::{
+    array::CFArray,
+    base::{CFType, TCFType, ToVoid},
+    dictionary::CFDictionary,
+    number::CFNumber,
+    string::CFString,
+};
+use std::{error::Error, fmt};
+
+/// A VLAN tag, also known as the VLAN ID. Always within the valid range 1-4094.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct VlanTag(u16);
+
+impl VlanTag {
+    /// The smallest valid tag.
+    pub const MIN: u16 = 1;
+    /// The largest valid tag. 4095 is reserved.
+    pub const MAX: u16 = 4094;
+
+    /// Returns the tag, or an error if it is outside the valid range.
+    pub fn new(tag: u16) -> Result<Self, InvalidVlanTagError> {
+        if (Self::MIN..=Self::MAX).contains(&tag) {
+            Ok(VlanTag(tag))
+        } else {
+            Err(InvalidVlanTagError(tag))
+        }
+    }
+
+    /// Returns the tag as a number.
+    pub fn get(self) -> u16 {
+        self.0
+    }
+}
+
+impl fmt::Display for VlanTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// Error returned by [`VlanTag::new`] for tags outside the valid range 1-4094.
+///
+/// [`VlanTag::new`]: struct.VlanTag.html#method.new
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidVlanTagError(u16);
+
+impl InvalidVlanTagError {
+    /// Returns the rejected tag.
+    pub fn tag(&self) -> u16 {
+        self.0
+    }
+}
+
+impl fmt::Display for InvalidVlanTagError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "VLAN tag {} is outside the valid range {}-{}",
+            self.0,
+            VlanTag::MIN,
+            VlanTag::MAX
+        )
+    }
+}
+
+impl Error for InvalidVlanTagError {}
+
+core_foundation::declare_TCFType!(
+    /// A virtual LAN interface, which tags the traffic of a physical interface with a VLAN tag.
+    ///
+    /// Changes are made to the preferences the interface belongs to. Lock the preferences with
+    /// [`SCPreferences::lock`] and pass the transaction where an `&SCPreferences` is expected to
+    /// make several changes and commit them together.
+    ///
+    /// VLANs are network interfaces with the same Core Foundation type, so `downcast` from a
+    /// `CFType` succeeds for any network interface. Use [`from_interface`] to check that an
+    /// interface is a VLAN.
+    ///
+    /// See [`SCVLANInterfaceRef`] for details.
+    ///
+    /// [`from_interface`]: #method.from_interface
+    /// [`SCPreferences::lock`]: ../preferences/struct.SCPreferences.html#method.lock
+    /// [`SCVLANInterfaceRef`]: https://developer.apple.com/documentation/systemconfiguration/scvlaninterfaceref?language=objc
+    SCVLANInterface,
+    SCVLANInterfaceRef
+);
+core_foundation::impl_TCFType!(
+    SCVLANInterface,
+    SCVLANInterfaceRef,
+    SCNetworkInterfaceGetTypeID
+);
+
+impl SCVLANInterface {
+    /// Returns all VLAN interfaces in the preferences.
+    ///
+    /// See [`SCVLANInterfaceCopyAll`] for details.
+    ///
+    /// [`SCVLANInterfaceCopyAll`]: https://developer.apple.com/documentation/systemconfiguration/1516905-scvlaninterfacecopyall?language=objc
+    pub fn get_all(prefs: &SCPreferences) -> Result<CFArray<Self>, SCError> {
+        unsafe {
+            let array_ptr = SCVLANInterfaceCopyAll(prefs.to_void());
+            if array_ptr.is_null() {
+                return Err(SCError::last());
+            }
+            Ok(CFArray::<Self>::wrap_under_create_rule(array_ptr))
+        }
+    }
+
+    /// Returns the physical interfaces that a VLAN can be created on.
+    ///
+    /// See [`SCVLANInterfaceCopyAvailablePhysicalInterfaces`] for details.
+    ///
+    /// [`SCVLANInterfaceCopyAvailablePhysicalInterfaces`]: https://developer.apple.com/documentation/systemconfiguration/1517004-scvlaninterfacecopyavailablephys?language=objc
+    pub fn available_physical_interfaces() -> Result<CFArray<SCNetworkInterface>, SCError> {
+        unsafe {
+            let array_ptr = SCVLANInterfaceCopyAvailablePhysicalInterfaces();
+            if array_ptr.is_null() {
+                return Err(SCError::last());
+            }
+            Ok(CFArray::<SCNetworkInterface>::wrap_under_create_rule(
+                array_ptr,
+            ))
+        }
+    }
+
+    /// Creates a new VLAN interface on the physical interface in the preferences.
+    ///
+    /// See [`SCVLANInterfaceCreate`] for details.
+    ///
+    /// [`SCVLANInterfaceCreate`]: https://developer.apple.com/documentation/systemconfiguration/1517094-scvlaninterfacecreate?language=objc
+    pub fn create(
+        prefs: &SCPreferences,
+        physical: &SCNetworkInterface,
+        tag: VlanTag,
+    ) -> Result<Self, SCError> {
+        let tag = CFNumber::from(i32::from(tag.get()));
+        unsafe {
+            let vlan_ref = SCVLANInterfaceCreate(
+                prefs.to_void(),
+                physical.as_concrete_TypeRef(),
+                tag.as_concrete_TypeRef(),
+            );
+            if vlan_ref.is_null() {
+                Err(SCError::last())
+            } else {
+                Ok(Self::wrap_under_create_rule(vlan_ref))
+            }
+        }
+    }
+
+    /// Removes the VLAN interface from the preferences it was read from.
+    ///
+    /// See [`SCVLANInterfaceRemove`] for details.
+    ///
+    /// [`SCVLANInterfaceRemove`]: https://developer.apple.com/documentation/systemconfiguration/1516934-scvlaninterfaceremove?language=objc
+    pub fn remove(self) -> Result<(), SCError> {
+        SCError::check(unsafe { SCVLANInterfaceRemove(self.0) })
+    }
+
+    /// Returns the interface as a VLAN, or `None` if it is not a VLAN interface.
+    pub fn from_interface(interface: &SCNetworkInterface) -> Option<Self> {
+        match interface.interface_type()? {
+            SCNetworkInterfaceType::VLAN => unsafe {
+                Some(Self::wrap_under_get_rule(interface.as_concrete_TypeRef()))
+            },
+            _ => None,
+        }
+    }
+
+    /// Returns the VLAN as a generic network interface.
+    pub fn as_network_interface(&self) -> SCNetworkInterface {
+        unsafe { SCNetworkInterface::wrap_under_get_rule(self.0) }
+    }
+
+    /// Returns the physical interface the VLAN is on.
+    ///
+    /// See [`SCVLANInterfaceGetPhysicalInterface`] for details.
+    ///
+    /// [`SCVLANInterfaceGetPhysicalInterface`]: https://developer.apple.com/documentation/systemconfiguration/1517193-scvlaninterfacegetphysicalinterf?language=objc
+    pub fn physical_interface(&self) -> Option<SCNetworkInterface> {
+        unsafe {
+            let ptr = SCVLANInterfaceGetPhysicalInterface(self.0);
+            if ptr.is_null() {
+                None
+            } else {
+                Some(SCNetworkInterface::wrap_under_get_rule(ptr))
+            }
+        }
+    }
+
+    /// Returns the tag of the VLAN, or `None` if it has no valid tag.
+    ///
+    /// See [`SCVLANInterfaceGetTag`] for details.
+    ///
+    /// [`SCVLANInterfaceGetTag`]: https://developer.apple.com/documentation/systemconfiguration/1516862-scvlaninterfacegettag?language=objc
+    pub fn tag(&self) -> Option<VlanTag> {
+        let tag = unsafe {
+            let ptr = SCVLANInterfaceGetTag(self.0);
+            if ptr.is_null() {
+                return None;
+            }
+            CFNumber::wrap_under_get_rule(ptr)
+        };
+        let tag = u16::try_from(tag.to_i64()?).ok()?;
+        VlanTag::new(tag).ok()
+    }
+
+    /// Moves the VLAN to the given physical interface and tag.
+    ///
+    /// See [`SCVLANInterfaceSetPhysicalInterfaceAndTag`] for details.
+    ///
+    /// [`SCVLANInterfaceSetPhysicalInterfaceAndTag`]: https://developer.apple.com/documentation/systemconfiguration/1516851-scvlaninterfacesetphysicalinterf?language=objc
+    pub fn set_physical_interface_and_tag(
+        &self,
+        physical: &SCNetworkInterface,
+        tag: VlanTag,
+    ) -> Result<(), SCError> {
+        let tag = CFNumber::from(i32::from(tag.get()));
+        SCError::check(unsafe {
+            SCVLANInterfaceSetPhysicalInterfaceAndTag(
+                self.0,
+                physical.as_concrete_TypeRef(),
+                tag.as_concrete_TypeRef(),
+            )
+        })
+    }
+
+    /// Returns the configuration options of the VLAN, or `None` if it has none.
+    ///
+    /// See [`SCVLANInterfaceGetOptions`] for details.
+    ///
+    /// [`SCVLANInterfaceGetOptions`]: https://developer.apple.com/documentation/systemconfiguration/1517249-scvlaninterfacegetoptions?language=objc
+    pub fn options(&self) -> Option<CFDictionary<CFString, CFType>> {
+        unsafe {
+            let dict_ptr = SCVLANInterfaceGetOptions(self.0);
+            if dict_ptr.is_null() {
+                None
+            } else {
+                Some(CFDictionary::wrap_under_get_rule(dict_ptr))
+            }
+        }
+    }
+
+    /// Replaces the configuration options of the VLAN.
+    ///
+    /// See [`SCVLANInterfaceSetOptions`] for details.
+    ///
+    /// [`SCVLANInterfaceSetOptions`]: https://developer.apple.com/documentation/systemconfiguration/1516922-scvlaninterfacesetoptions?language=objc
+    pub fn set_options(&self, options: &CFDictionary<CFString, CFType>) -> Result<(), SCError> {
+        SCError::check(unsafe { SCVLANInterfaceSetOptions(self.0, options.as_concrete_TypeRef()) })
+    }
+
+    /// Sets the localized display name of the VLAN.
+    ///
+    /// See [`SCVLANInterfaceSetLocalizedDisplayName`] for details.
+    ///
+    /// [`SCVLANInterfaceSetLocalizedDisplayName`]: https://developer.apple.com/documentation/systemconfiguration/1517211-scvlaninterfacesetlocalizeddispl?language=objc
+    pub fn set_display_name(&self, name: &CFString) -> Result<(), SCError> {
+        SCError::check(unsafe {
+            SCVLANInterfaceSetLocalizedDisplayName(self.0, name.as_concrete_TypeRef())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tag_range() {
+        assert_eq!(VlanTag::new(1).map(VlanTag::get), Ok(1));
+        assert_eq!(VlanTag::new(4094).map(VlanTag::get), Ok(4094));
+        assert_eq!(VlanTag::new(0), Err(InvalidVlanTagError(0)));
+        assert_eq!(VlanTag::new(4095).unwrap_err().tag(), 4095);
+        assert_eq!(
+            VlanTag::new(5000).unwrap_err().to_string(),
+            "VLAN tag 5000 is outside the valid range 1-4094"
+        );
+    }
+
+    #[test]
+    fn create_in_transaction() {
+        let physical = match SCVLANInterface::available_physical_interfaces()
+            .unwrap()
+            .iter()
+            .next()
+        {
+            Some(physical) => physical.clone(),
+            None => return,
+        };
+        let path = std::env::temp_dir().join(format!(
+            "system-configuration-rs-vlan-{}.plist",
+            std::process::id()
+        ));
+        let preferences = SCPreferences::group(
+            &CFString::new("test"),
+            &CFString::new(path.to_str().unwrap()),
+        )
+        .unwrap();
+
+        let transaction = preferences.lock(false).unwrap();
+        let tag = VlanTag::new(42).unwrap();
+        let vlan = SCVLANInterface::create(&transaction, &physical, tag).unwrap();
+        assert_eq!(vlan.tag(), Some(tag));
+        assert!(SCVLANInterface::from_interface(&vlan.as_network_interface()).is_some());
+        assert!(SCVLANInterface::from_interface(&physical).is_none());
+        assert!(vlan.physical_interface() == Some(physical));
+        assert_eq!(SCVLANInterface::get_all(&transaction).unwrap().len(), 1);
+
+        vlan.remove().unwrap();
+        assert_eq!(SCVLANInterface::get_all(&transaction).unwrap().len(), 0);
+        drop(transaction);
+        let _ = std::fs::remove_file(path);
+    }
+}
@@ -12,10 +12,27 @@
 //!
 //! [`SCPreferences`]: https://developer.apple.com/documentation/systemconfiguration/scpreferences-ft8
 
-use crate::sys::preferences::{SCPreferencesCreate, SCPreferencesGetTypeID, SCPreferencesRef};
+mod notification;
+mod path;
+mod signature;
+
+pub use self::notification::PreferencesNotification;
+pub use self::path::{PathError, PathErrorKind, PreferencesPath};
+pub use self::signature::{CommitError, PreferencesSignature};
+
+use crate::authorization::Authorization;
+use crate::error::SCError;
+use crate::sys::preferences::{
+    SCPreferencesAddValue, SCPreferencesApplyChanges, SCPreferencesCommitChanges,
+    SCPreferencesCopyKeyList, SCPreferencesCreate, SCPreferencesCreateWithAuthorization,
+    SCPreferencesGetTypeID, SCPreferencesGetValue, SCPreferencesLock, SCPreferencesRef,
+    SCPreferencesRemoveValue, SCPreferencesSetValue, SCPreferencesUnlock,
+};
+use core_foundation::array::CFArray;
 use core_foundation::base::{CFAllocator, TCFType};
+use core_foundation::propertylist::{CFPropertyList, CFPropertyListSubClass};
 use core_foundation::string::CFString;
-use std::ptr;
+use std::{mem, ops::Deref, ptr};
 
 declare_TCFType! {
     /// The handle to an open preferences session for accessing system configuration preferences.
@@ -26,7 +43,7 @@ impl_TCFType!(SCPreferences, SCPreferencesRef, SCPreferencesGetTypeID);
 
 impl SCPreferences {
     /// Initiates access to the default system preferences using the default allocator.
-    pub fn default(calling_process_name: &CFString) -> Self {
+    pub fn default(calling_process_name: &CFString) -> Result<Self, SCError> {
         Self::new(None, calling_process_name, None)
     }
 
@@ -35,14 +52,15 @@ impl SCPreferences {
     /// constructor.
     ///
     /// [`default`]: #method.default
-    pub fn group(calling_process_name: &CFString, prefs_id: &CFString) -> Self {
+    pub fn group(calling_process_name: &CFString, prefs_id: &CFString) -> Result<Self, SCError> {
         Self::new(None, calling_process_name, Some(prefs_id))
     }
 
     /// Initiates access to the per-system set of configuration preferences with a given
     /// allocator and preference group to access. See the underlying [SCPreferencesCreate] function
     /// documentation for details. Use the helper constructors [`default`] and [`group`] to easier
-    /// create an instance using the default allocator.
+    /// create an instance using the default allocator. Fails with the error reported by the
+    /// framework if the session could not be created.
     ///
     /// [SCPreferencesCreate]: https://developer.apple.com/documentation/systemconfiguration/1516807-scpreferencescreate?language=objc
     /// [`default`]: #method.default
@@ -51,7 +69,39 @@ impl SCPreferences {
         allocator: Option<&CFAllocator>,
         calling_process_name: &CFString,
         prefs_id: Option<&CFString>,
-    ) -> Self {
+    ) -> Result<Self, SCError> {
+        let allocator_ref = match allocator {
+            Some(allocator) => allocator.as_concrete_TypeRef(),
+            None => ptr::null(),
+        };
+        let prefs_id_ref = match prefs_id {
+            Some(prefs_id) => prefs_id.as_concrete_TypeRef(),
+            None => ptr::null(),
+        };
+
+        unsafe {
+            Self::from_create_rule(SCPreferencesCreate(
+                allocator_ref,
+                calling_process_name.as_concrete_TypeRef(),
+                prefs_id_ref,
+            ))
+        }
+    }
+
+    /// Initiates access to the preferences on behalf of the user that authorized `authorization`,
+    /// instead of the user running this process. Lets a process that does not run as root commit
+    /// and apply changes, given the right was granted. See [`new`] for the other arguments.
+    ///
+    /// See [`SCPreferencesCreateWithAuthorization`] for details.
+    ///
+    /// [`new`]: #method.new
+    /// [`SCPreferencesCreateWithAuthorization`]: https://developer.apple.com/documentation/systemconfiguration/1516805-scpreferencescreatewithauthoriza?language=objc
+    pub fn with_authorization(
+        allocator: Option<&CFAllocator>,
+        calling_process_name: &CFString,
+        prefs_id: Option<&CFString>,
+        authorization: &Authorization,
+    ) -> Result<Self, SCError> {
         let allocator_ref = match allocator {
             Some(allocator) => allocator.as_concrete_TypeRef(),
             None => ptr::null(),
@@ -62,13 +112,213 @@ impl SCPreferences {
         };
 
         unsafe {
-            SCPreferences::wrap_under_create_rule(SCPreferencesCreate(
+            Self::from_create_rule(SCPreferencesCreateWithAuthorization(
                 allocator_ref,
                 calling_process_name.as_concrete_TypeRef(),
                 prefs_id_ref,
+                authorization.as_raw(),
             ))
         }
     }
+
+    /// Wraps a newly created session, or returns the error of the failed call if it is NULL.
+    unsafe fn from_create_rule(prefs_ref: SCPreferencesRef) -> Result<Self, SCError> {
+        if prefs_ref.is_null() {
+            Err(SCError::last())
+        } else {
+            Ok(Self::wrap_under_create_rule(prefs_ref))
+        }
+    }
+
+    /// Locks the preferences for exclusive access, and returns a transaction that keeps them
+    /// locked until it is dropped. With `wait` set, blocks until other processes release the lock.
+    /// Otherwise fails with [`SCError::Locked`] if another process holds it.
+    ///
+    /// Fails with [`SCError::Stale`] if the preferences were changed by another process since
+    /// this session read them. Call [`synchronize`] and try again in that case.
+    ///
+    /// See [`SCPreferencesLock`] for details.
+    ///
+    /// [`SCError::Locked`]: ../error/enum.SCError.html#variant.Locked
+    /// [`SCError::Stale`]: ../error/enum.SCError.html#variant.Stale
+    /// [`synchronize`]: #method.synchronize
+    /// [`SCPreferencesLock`]: https://developer.apple.com/documentation/systemconfiguration/1516829-scpreferenceslock?language=objc
+    pub fn lock(&self, wait: bool) -> Result<PreferencesTransaction<'_>, SCError> {
+        SCError::check(unsafe { SCPreferencesLock(self.as_concrete_TypeRef(), wait as u8) })?;
+        Ok(PreferencesTransaction { preferences: self })
+    }
+
+    /// Returns the keys at the top level of the preferences, such as `NetworkServices`, `Sets`,
+    /// `System` and `CurrentSet`.
+    pub fn keys(&self) -> Result<CFArray<CFString>, SCError> {
+        unsafe {
+            let array_ref = SCPreferencesCopyKeyList(self.as_concrete_TypeRef());
+            if array_ref.is_null() {
+                Err(SCError::last())
+            } else {
+                Ok(CFArray::wrap_under_create_rule(array_ref))
+            }
+        }
+    }
+
+    /// If the given key exists in the preferences, the associated value is returned. Includes
+    /// changes staged in this session that are not yet committed.
+    ///
+    /// Use `CFPropertyList::downcast_into` to cast the result into the correct type.
+    pub fn get<S: Into<CFString>>(&self, key: S) -> Option<CFPropertyList> {
+        let cf_key = key.into();
+        unsafe {
+            let value_ref =
+                SCPreferencesGetValue(self.as_concrete_TypeRef(), cf_key.as_concrete_TypeRef());
+            if value_ref.is_null() {
+                None
+            } else {
+                Some(CFPropertyList::wrap_under_get_rule(value_ref))
+            }
+        }
+    }
+
+    /// Sets the value of the given key. Overwrites existing values. The change is staged in this
+    /// session until it is committed with a [`PreferencesTransaction`].
+    ///
+    /// [`PreferencesTransaction`]: struct.PreferencesTransaction.html
+    pub fn set<S: Into<CFString>, V: CFPropertyListSubClass>(
+        &self,
+        key: S,
+        value: V,
+    ) -> Result<(), SCError> {
+        self.set_raw(key, &value.into_CFPropertyList())
+    }
+
+    /// Sets the value of the given key. Overwrites existing values. The change is staged in this
+    /// session until it is committed.
+    pub fn set_raw<S: Into<CFString>>(
+        &self,
+        key: S,
+        value: &CFPropertyList,
+    ) -> Result<(), SCError> {
+        let cf_key = key.into();
+        SCError::check(unsafe {
+            SCPreferencesSetValue(
+                self.as_concrete_TypeRef(),
+                cf_key.as_concrete_TypeRef(),
+                value.as_concrete_TypeRef(),
+            )
+        })
+    }
+
+    /// Adds the given key-value pair, but only if the key does not already exist. Fails with
+    /// [`SCError::KeyExists`] if it does. The change is staged in this session until it is
+    /// committed.
+    ///
+    /// [`SCError::KeyExists`]: ../error/enum.SCError.html#variant.KeyExists
+    pub fn add<S: Into<CFString>, V: CFPropertyListSubClass>(
+        &self,
+        key: S,
+        value: V,
+    ) -> Result<(), SCError> {
+        self.add_raw(key, &value.into_CFPropertyList())
+    }
+
+    /// Adds the given key-value pair, but only if the key does not already exist. The change is
+    /// staged in this session until it is committed.
+    pub fn add_raw<S: Into<CFString>>(
+        &self,
+        key: S,
+        value: &CFPropertyList,
+    ) -> Result<(), SCError> {
+        let cf_key = key.into();
+        SCError::check(unsafe {
+            SCPreferencesAddValue(
+                self.as_concrete_TypeRef(),
+                cf_key.as_concrete_TypeRef(),
+                value.as_concrete_TypeRef(),
+            )
+        })
+    }
+
+    /// Removes the value of the given key. Fails with [`SCError::NoKey`] if the key does not
+    /// exist. The change is staged in this session until it is committed.
+    ///
+    /// [`SCError::NoKey`]: ../error/enum.SCError.html#variant.NoKey
+    pub fn remove<S: Into<CFString>>(&self, key: S) -> Result<(), SCError> {
+        let cf_key = key.into();
+        SCError::check(unsafe {
+            SCPreferencesRemoveValue(self.as_concrete_TypeRef(), cf_key.as_concrete_TypeRef())
+        })
+    }
+}
+
+/// An exclusive lock on an [`SCPreferences`] session. Edits made through it, or through the
+/// session it dereferences to, are staged in memory until they are committed.
+///
+/// The preferences are unlocked when the transaction is dropped, also when returning early with
+/// an error or unwinding from a panic. Changes that were not committed are not written, but stay
+/// staged in the session.
+///
+/// Created by [`SCPreferences::lock`].
+///
+/// [`SCPreferences`]: struct.SCPreferences.html
+/// [`SCPreferences::lock`]: struct.SCPreferences.html#method.lock
+pub struct PreferencesTransaction<'a> {
+    preferences: &'a SCPreferences,
+}
+
+impl<'a> PreferencesTransaction<'a> {
+    /// Writes the staged changes to persistent storage. They do not take effect until they are
+    /// applied.
+    ///
+    /// See [`SCPreferencesCommitChanges`] for details.
+    ///
+    /// [`SCPreferencesCommitChanges`]: https://developer.apple.com/documentation/systemconfiguration/1516866-scpreferencescommitchanges?language=objc
+    pub fn commit(&self) -> Result<(), SCError> {
+        SCError::check(unsafe {
+            SCPreferencesCommitChanges(self.preferences.as_concrete_TypeRef())
+        })
+    }
+
+    /// Requests that the currently committed changes take effect on the active configuration.
+    ///
+    /// See [`SCPreferencesApplyChanges`] for details.
+    ///
+    /// [`SCPreferencesApplyChanges`]: https://developer.apple.com/documentation/systemconfiguration/1516819-scpreferencesapplychanges?language=objc
+    pub fn apply(&self) -> Result<(), SCError> {
+        SCError::check(unsafe { SCPreferencesApplyChanges(self.preferences.as_concrete_TypeRef()) })
+    }
+
+    /// Commits and applies the staged changes, then releases the lock. The lock is released even
+    /// if committing or applying fails.
+    pub fn commit_and_apply(self) -> Result<(), SCError> {
+        self.commit()?;
+        self.apply()?;
+        self.unlock()
+    }
+
+    /// Releases the lock. Same as dropping the transaction, but reports whether unlocking
+    /// succeeded.
+    ///
+    /// See [`SCPreferencesUnlock`] for details.
+    ///
+    /// [`SCPreferencesUnlock`]: https://developer.apple.com/documentation/systemconfiguration/1516810-scpreferencesunlock?language=objc
+    pub fn unlock(self) -> Result<(), SCError> {
+        let preferences = self.preferences;
+        mem::forget(self);
+        SCError::check(unsafe { SCPreferencesUnlock(preferences.as_concrete_TypeRef()) })
+    }
+}
+
+impl<'a> Deref for PreferencesTransaction<'a> {
+    type Target = SCPreferences;
+
+    fn deref(&self) -> &SCPreferences {
+        self.preferences
+    }
+}
+
+impl<'a> Drop for PreferencesTransaction<'a> {
+    fn drop(&mut self) {
+        unsafe { SCPreferencesUnlock(self.preferences.as_concrete_TypeRef()) };
+    }
 }
 
 #[cfg(test)]
@@ -77,7 +327,61 @@ mod tests {
 
     #[test]
     fn retain_count() {
-        let preferences = SCPreferences::default(&CFString::new("test"));
+        let preferences = SCPreferences::default(&CFString::new("test")).unwrap();
         assert_eq!(preferences.retain_count(), 1);
     }
+
+    #[test]
+    fn transaction_unlocks_on_drop() {
+        let path = std::env::temp_dir().join(format!(
+            "system-configuration-rs-{}.plist",
+            std::process::id()
+        ));
+        let preferences = SCPreferences::group(
+            &CFString::new("test"),
+            &CFString::new(path.to_str().unwrap()),
+        )
+        .unwrap();
+
+        let transaction = preferences.lock(false).unwrap();
+        assert_eq!(preferences.lock(false).err(), Some(SCError::Locked));
+        transaction.commit().unwrap();
+        drop(transaction);
+
+        preferences.lock(false).unwrap().unlock().unwrap();
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn get_set_add_remove() {
+        let path = std::env::temp_dir().join(format!(
+            "system-configuration-rs-values-{}.plist",
+            std::process::id()
+        ));
+        let preferences = SCPreferences::group(
+            &CFString::new("test"),
+            &CFString::new(path.to_str().unwrap()),
+        )
+        .unwrap();
+
+        preferences.set("Name", CFString::new("first")).unwrap();
+        assert_eq!(
+            preferences.add("Name", CFString::new("second")).err(),
+            Some(SCError::KeyExists)
+        );
+        let name = preferences
+            .get("Name")
+            .and_then(CFPropertyList::downcast_into::<CFString>)
+            .unwrap();
+        assert_eq!(name.to_string(), "first");
+        assert!(preferences
+            .keys()
+            .unwrap()
+            .iter()
+            .any(|key| key.to_string() == "Name"));
+
+        preferences.remove("Name").unwrap();
+        assert!(preferences.get("Name").is_none());
+        assert_eq!(preferences.remove("Name").err(), Some(SCError::NoKey));
+    }
 }
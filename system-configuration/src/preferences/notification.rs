@@ -0,0 +1,206 @@
+//! Notifications of changes to the preferences, made by this or any other process.
+
+use super::SCPreferences;
+use crate::{
+    dispatch::DispatchQueue,
+    error::SCError,
+    sys::preferences::{
+        kSCPreferencesNotificationApply, kSCPreferencesNotificationCommit, SCPreferencesContext,
+        SCPreferencesNotification, SCPreferencesRef, SCPreferencesScheduleWithRunLoop,
+        SCPreferencesSetCallback, SCPreferencesSetDispatchQueue,
+        SCPreferencesUnscheduleFromRunLoop,
+    },
+};
+use core_foundation::{
+    base::TCFType,
+    runloop::CFRunLoop,
+    string::{CFString, CFStringRef},
+};
+use std::{ffi::c_void, ptr, sync::Arc};
+
+bitflags::bitflags! {
+    /// The kinds of changes reported to a preferences callback.
+    ///
+    /// See [`SCPreferencesNotification`] for details.
+    ///
+    /// [`SCPreferencesNotification`]: https://developer.apple.com/documentation/systemconfiguration/scpreferencesnotification?language=objc
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct PreferencesNotification: u32 {
+        /// Changes to the preferences were committed to persistent storage.
+        const COMMIT = kSCPreferencesNotificationCommit;
+        /// A request was made to apply the committed changes to the active configuration.
+        const APPLY = kSCPreferencesNotificationApply;
+    }
+}
+
+impl SCPreferences {
+    /// Sets a callback that is called when the preferences are committed or applied. For the
+    /// callback to be called, the preferences must be scheduled on a run loop or dispatch queue.
+    /// Replaces any callback set earlier.
+    ///
+    /// See [`SCPreferencesSetCallback`] for details.
+    ///
+    /// [`SCPreferencesSetCallback`]: https://developer.apple.com/documentation/systemconfiguration/1516809-scpreferencessetcallback?language=objc
+    pub fn set_callback<F>(&self, callback: F) -> Result<(), SCError>
+    where
+        F: Fn(&SCPreferences, PreferencesNotification) + Send + Sync + 'static,
+    {
+        let callback = Arc::new(PreferencesCallbackContext { callback });
+        let mut context = SCPreferencesContext {
+            version: 0,
+            info: Arc::into_raw(callback) as *mut _,
+            retain: Some(PreferencesCallbackContext::<F>::retain_context),
+            release: Some(PreferencesCallbackContext::<F>::release_context),
+            copyDescription: Some(PreferencesCallbackContext::<F>::copy_ctx_description),
+        };
+
+        let result = SCError::check(unsafe {
+            SCPreferencesSetCallback(
+                self.as_concrete_TypeRef(),
+                Some(PreferencesCallbackContext::<F>::callback),
+                &mut context,
+            )
+        });
+
+        // The preferences retain the context with the `retain` callback, and release it when the
+        // callback is replaced or the preferences are deallocated. Drop the reference taken by
+        // `Arc::into_raw` above.
+        unsafe {
+            Arc::decrement_strong_count(context.info as *const PreferencesCallbackContext<F>)
+        };
+        result
+    }
+
+    /// Removes the callback set with [`set_callback`].
+    ///
+    /// [`set_callback`]: #method.set_callback
+    pub fn clear_callback(&self) -> Result<(), SCError> {
+        SCError::check(unsafe {
+            SCPreferencesSetCallback(self.as_concrete_TypeRef(), None, ptr::null_mut())
+        })
+    }
+
+    /// Schedule the callback with a run loop.
+    ///
+    /// See [`SCPreferencesScheduleWithRunLoop`] for details.
+    ///
+    /// [`SCPreferencesScheduleWithRunLoop`]: https://developer.apple.com/documentation/systemconfiguration/1516802-scpreferencesschedulewithrunloop?language=objc
+    ///
+    /// # Safety
+    ///
+    /// The `run_loop_mode` must not be NULL and must be a pointer to a valid run loop mode.
+    /// Use `core_foundation::runloop::kCFRunLoopCommonModes` if you are unsure.
+    pub unsafe fn schedule_with_runloop(
+        &self,
+        run_loop: &CFRunLoop,
+        run_loop_mode: CFStringRef,
+    ) -> Result<(), SCError> {
+        SCError::check(SCPreferencesScheduleWithRunLoop(
+            self.as_concrete_TypeRef(),
+            run_loop.as_concrete_TypeRef(),
+            run_loop_mode,
+        ))
+    }
+
+    /// Unschedule the callback from a run loop.
+    ///
+    /// See [`SCPreferencesUnscheduleFromRunLoop`] for details.
+    ///
+    /// [`SCPreferencesUnscheduleFromRunLoop`]: https://developer.apple.com/documentation/systemconfiguration/1516813-scpreferencesunschedulefromrunlo?language=objc
+    ///
+    /// # Safety
+    ///
+    /// The `run_loop_mode` must not be NULL and must be a pointer to a valid run loop mode.
+    /// Use `core_foundation::runloop::kCFRunLoopCommonModes` if you are unsure.
+    pub unsafe fn unschedule_from_runloop(
+        &self,
+        run_loop: &CFRunLoop,
+        run_loop_mode: CFStringRef,
+    ) -> Result<(), SCError> {
+        SCError::check(SCPreferencesUnscheduleFromRunLoop(
+            self.as_concrete_TypeRef(),
+            run_loop.as_concrete_TypeRef(),
+            run_loop_mode,
+        ))
+    }
+
+    /// Deliver the callback on the given dispatch queue instead of a run loop, or stop delivering
+    /// it on a queue when `queue` is `None`. The queue is retained until it is replaced.
+    ///
+    /// See [`SCPreferencesSetDispatchQueue`] for details.
+    ///
+    /// [`SCPreferencesSetDispatchQueue`]: https://developer.apple.com/documentation/systemconfiguration/1516817-scpreferencessetdispatchqueue?language=objc
+    pub fn set_dispatch_queue(&self, queue: Option<&DispatchQueue>) -> Result<(), SCError> {
+        let queue_ref = queue.map_or(ptr::null_mut(), DispatchQueue::as_raw);
+        SCError::check(unsafe {
+            SCPreferencesSetDispatchQueue(self.as_concrete_TypeRef(), queue_ref)
+        })
+    }
+}
+
+struct PreferencesCallbackContext<F> {
+    callback: F,
+}
+
+impl<F> PreferencesCallbackContext<F>
+where
+    F: Fn(&SCPreferences, PreferencesNotification) + Send + Sync + 'static,
+{
+    unsafe extern "C" fn callback(
+        prefs_ref: SCPreferencesRef,
+        notification_type: SCPreferencesNotification,
+        info: *mut c_void,
+    ) {
+        let context = &*(info as *const Self);
+        let preferences = SCPreferences::wrap_under_get_rule(prefs_ref);
+        (context.callback)(
+            &preferences,
+            PreferencesNotification::from_bits_retain(notification_type),
+        );
+    }
+
+    unsafe extern "C" fn copy_ctx_description(_ctx: *const c_void) -> CFStringRef {
+        let description = CFString::from_static_string("SCPreferences's callback context");
+        let description_ref = description.as_concrete_TypeRef();
+        std::mem::forget(description);
+        description_ref
+    }
+
+    unsafe extern "C" fn release_context(ctx: *const c_void) {
+        Arc::decrement_strong_count(ctx as *const Self);
+    }
+
+    unsafe extern "C" fn retain_context(ctx: *const c_void) -> *const c_void {
+        Arc::increment_strong_count(ctx as *const Self);
+        ctx
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dispatch::QualityOfService;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn callback_context_is_released() {
+        let preferences =
+            SCPreferences::default(&CFString::new("callback_context_is_released")).unwrap();
+        let tracker = Arc::new(AtomicUsize::new(0));
+
+        let callback_tracker = tracker.clone();
+        preferences
+            .set_callback(move |_, _| {
+                callback_tracker.fetch_add(1, Ordering::SeqCst);
+            })
+            .unwrap();
+        assert_eq!(Arc::strong_count(&tracker), 2);
+
+        let queue = DispatchQueue::global(QualityOfService::Default);
+        preferences.set_dispatch_queue(Some(&queue)).unwrap();
+        preferences.set_dispatch_queue(None).unwrap();
+
+        preferences.clear_callback().unwrap();
+        assert_eq!(Arc::strong_count(&tracker), 1);
+    }
+}
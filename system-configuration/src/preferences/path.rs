@@ -0,0 +1,406 @@
+//! Paths into the tree of nested dictionaries that make up the preferences.
+//!
+//! A path such as `/Sets/<set id>/Network/Service/<service id>` names a dictionary, reached by
+//! looking up each segment in the dictionary of its parent. Dictionaries can also be links to
+//! other paths, which SystemConfiguration follows when looking up a path.
+//! See [`SCPreferencesPath`] for details.
+//!
+//! [`SCPreferencesPath`]: https://developer.apple.com/documentation/systemconfiguration/scpreferencespath?language=objc
+
+use super::SCPreferences;
+use crate::{
+    error::SCError,
+    sys::preferences_path::{
+        SCPreferencesPathCreateUniqueChild, SCPreferencesPathGetLink, SCPreferencesPathGetValue,
+        SCPreferencesPathRemoveValue, SCPreferencesPathSetLink, SCPreferencesPathSetValue,
+    },
+};
+use core_foundation::{
+    base::{CFType, TCFType},
+    dictionary::CFDictionary,
+    string::{CFString, CFStringRef},
+};
+use std::{
+    error::Error,
+    fmt::{self, Display},
+    str::FromStr,
+};
+
+const SEPARATOR: char = '/';
+
+/// A validated, absolute path in the preferences.
+///
+/// ```
+/// # use system_configuration::preferences::PreferencesPath;
+/// let service = PreferencesPath::root()
+///     .join("NetworkServices")
+///     .and_then(|path| path.join("0E8F3A6C-4B5D-4A1B-9C2E-3F7D8A9B0C1D"))
+///     .unwrap();
+/// assert_eq!(
+///     service.as_str(),
+///     "/NetworkServices/0E8F3A6C-4B5D-4A1B-9C2E-3F7D8A9B0C1D"
+/// );
+/// assert_eq!(service.name(), Some("0E8F3A6C-4B5D-4A1B-9C2E-3F7D8A9B0C1D"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PreferencesPath(String);
+
+impl PreferencesPath {
+    /// Returns the root path, `/`, naming the top level dictionary of the preferences.
+    pub fn root() -> Self {
+        PreferencesPath(SEPARATOR.to_string())
+    }
+
+    /// Returns this path extended with a single `segment`. Fails if the segment is empty or
+    /// contains a `/`.
+    pub fn join(&self, segment: &str) -> Result<Self, PathError> {
+        let mut path = self.0.clone();
+        if !self.is_root() {
+            path.push(SEPARATOR);
+        }
+        path.push_str(segment);
+        let error = |kind| {
+            Err(PathError {
+                path: path.clone(),
+                kind,
+            })
+        };
+        if segment.is_empty() {
+            return error(PathErrorKind::EmptySegment);
+        }
+        if segment.contains(SEPARATOR) {
+            return error(PathErrorKind::SeparatorInSegment);
+        }
+        Ok(PreferencesPath(path))
+    }
+
+    /// Returns the path as a string.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Returns `true` if this is the root path.
+    pub fn is_root(&self) -> bool {
+        self.0.len() == 1
+    }
+
+    /// Returns the segments of the path. The root path has no segments.
+    pub fn segments(&self) -> impl Iterator<Item = &str> {
+        self.0[1..]
+            .split(SEPARATOR)
+            .filter(|segment| !segment.is_empty())
+    }
+
+    /// Returns the path without its last segment, or `None` for the root path.
+    pub fn parent(&self) -> Option<Self> {
+        if self.is_root() {
+            return None;
+        }
+        let end = self.0.rfind(SEPARATOR).unwrap_or(0).max(1);
+        Some(PreferencesPath(self.0[..end].to_owned()))
+    }
+
+    /// Returns the last segment of the path, or `None` for the root path.
+    pub fn name(&self) -> Option<&str> {
+        self.segments().last()
+    }
+}
+
+impl Display for PreferencesPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl FromStr for PreferencesPath {
+    type Err = PathError;
+
+    fn from_str(path: &str) -> Result<Self, Self::Err> {
+        let error = |kind| {
+            Err(PathError {
+                path: path.to_owned(),
+                kind,
+            })
+        };
+        let relative = match path.strip_prefix(SEPARATOR) {
+            Some(relative) => relative,
+            None => return error(PathErrorKind::NotAbsolute),
+        };
+        if !relative.is_empty() && relative.split(SEPARATOR).any(str::is_empty) {
+            return error(PathErrorKind::EmptySegment);
+        }
+        Ok(PreferencesPath(path.to_owned()))
+    }
+}
+
+impl From<&PreferencesPath> for CFString {
+    fn from(path: &PreferencesPath) -> Self {
+        CFString::new(path.as_str())
+    }
+}
+
+/// Failure to create a [`PreferencesPath`].
+///
+/// [`PreferencesPath`]: struct.PreferencesPath.html
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathError {
+    path: String,
+    kind: PathErrorKind,
+}
+
+impl PathError {
+    /// Returns the invalid path.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Returns the reason the path is invalid.
+    pub fn kind(&self) -> PathErrorKind {
+        self.kind
+    }
+}
+
+impl Display for PathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Invalid preferences path \"{}\": {}",
+            self.path, self.kind
+        )
+    }
+}
+
+impl Error for PathError {}
+
+/// The reasons a [`PreferencesPath`] can be invalid.
+///
+/// [`PreferencesPath`]: struct.PreferencesPath.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathErrorKind {
+    /// The path does not start with `/`.
+    NotAbsolute,
+    /// The path contains an empty segment, such as in `/Sets//Network` or `/Sets/`.
+    EmptySegment,
+    /// A segment passed to [`PreferencesPath::join`] contains a `/`.
+    ///
+    /// [`PreferencesPath::join`]: struct.PreferencesPath.html#method.join
+    SeparatorInSegment,
+}
+
+impl Display for PathErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let description = match self {
+            PathErrorKind::NotAbsolute => "the path must start with '/'",
+            PathErrorKind::EmptySegment => "empty segment",
+            PathErrorKind::SeparatorInSegment => "a segment can not contain '/'",
+        };
+        f.write_str(description)
+    }
+}
+
+impl SCPreferences {
+    /// Creates a new dictionary below `prefix`, named with a unique ID, and returns its path.
+    ///
+    /// See [`SCPreferencesPathCreateUniqueChild`] for details.
+    ///
+    /// [`SCPreferencesPathCreateUniqueChild`]: https://developer.apple.com/documentation/systemconfiguration/1516796-scpreferencespathcreateuniquechi?language=objc
+    pub fn create_unique_child(
+        &self,
+        prefix: &PreferencesPath,
+    ) -> Result<PreferencesPath, SCError> {
+        let prefix = CFString::from(prefix);
+        let path = unsafe {
+            wrap_string(SCPreferencesPathCreateUniqueChild(
+                self.as_concrete_TypeRef(),
+                prefix.as_concrete_TypeRef(),
+            ))
+        }
+        .ok_or_else(SCError::last)?;
+        Ok(PreferencesPath(path.to_string()))
+    }
+
+    /// Returns the dictionary at `path`, following links. Or `None` if there is no dictionary
+    /// at the path.
+    ///
+    /// See [`SCPreferencesPathGetValue`] for details.
+    ///
+    /// [`SCPreferencesPathGetValue`]: https://developer.apple.com/documentation/systemconfiguration/1516806-scpreferencespathgetvalue?language=objc
+    pub fn get_path_value(&self, path: &PreferencesPath) -> Option<CFDictionary<CFString, CFType>> {
+        let path = CFString::from(path);
+        unsafe {
+            let dictionary_ref =
+                SCPreferencesPathGetValue(self.as_concrete_TypeRef(), path.as_concrete_TypeRef());
+            if dictionary_ref.is_null() {
+                None
+            } else {
+                Some(CFDictionary::wrap_under_get_rule(dictionary_ref))
+            }
+        }
+    }
+
+    /// Returns the path that the dictionary at `path` links to, or `None` if it is not a link.
+    ///
+    /// See [`SCPreferencesPathGetLink`] for details.
+    ///
+    /// [`SCPreferencesPathGetLink`]: https://developer.apple.com/documentation/systemconfiguration/1516808-scpreferencespathgetlink?language=objc
+    pub fn get_path_link(&self, path: &PreferencesPath) -> Option<PreferencesPath> {
+        let path = CFString::from(path);
+        let link = unsafe {
+            let link_ref =
+                SCPreferencesPathGetLink(self.as_concrete_TypeRef(), path.as_concrete_TypeRef());
+            if link_ref.is_null() {
+                return None;
+            }
+            CFString::wrap_under_get_rule(link_ref)
+        };
+        link.to_string().parse().ok()
+    }
+
+    /// Sets the dictionary at `path`, creating any missing parent dictionaries. If the path is a
+    /// link, the dictionary it links to is replaced.
+    ///
+    /// See [`SCPreferencesPathSetValue`] for details.
+    ///
+    /// [`SCPreferencesPathSetValue`]: https://developer.apple.com/documentation/systemconfiguration/1516791-scpreferencespathsetvalue?language=objc
+    pub fn set_path_value<K, V>(
+        &self,
+        path: &PreferencesPath,
+        value: &CFDictionary<K, V>,
+    ) -> Result<(), SCError> {
+        let path = CFString::from(path);
+        SCError::check(unsafe {
+            SCPreferencesPathSetValue(
+                self.as_concrete_TypeRef(),
+                path.as_concrete_TypeRef(),
+                value.as_concrete_TypeRef(),
+            )
+        })
+    }
+
+    /// Makes `path` a link to the dictionary at `link`.
+    ///
+    /// See [`SCPreferencesPathSetLink`] for details.
+    ///
+    /// [`SCPreferencesPathSetLink`]: https://developer.apple.com/documentation/systemconfiguration/1516797-scpreferencespathsetlink?language=objc
+    pub fn set_path_link(
+        &self,
+        path: &PreferencesPath,
+        link: &PreferencesPath,
+    ) -> Result<(), SCError> {
+        let path = CFString::from(path);
+        let link = CFString::from(link);
+        SCError::check(unsafe {
+            SCPreferencesPathSetLink(
+                self.as_concrete_TypeRef(),
+                path.as_concrete_TypeRef(),
+                link.as_concrete_TypeRef(),
+            )
+        })
+    }
+
+    /// Removes the dictionary at `path`. If the path is a link, only the link is removed.
+    ///
+    /// See [`SCPreferencesPathRemoveValue`] for details.
+    ///
+    /// [`SCPreferencesPathRemoveValue`]: https://developer.apple.com/documentation/systemconfiguration/1516800-scpreferencespathremovevalue?language=objc
+    pub fn remove_path(&self, path: &PreferencesPath) -> Result<(), SCError> {
+        let path = CFString::from(path);
+        SCError::check(unsafe {
+            SCPreferencesPathRemoveValue(self.as_concrete_TypeRef(), path.as_concrete_TypeRef())
+        })
+    }
+}
+
+unsafe fn wrap_string(string_ref: CFStringRef) -> Option<CFString> {
+    if string_ref.is_null() {
+        None
+    } else {
+        Some(CFString::wrap_under_create_rule(string_ref))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let path = "/Sets/ABC/Network".parse::<PreferencesPath>().unwrap();
+        assert_eq!(
+            path.segments().collect::<Vec<_>>(),
+            ["Sets", "ABC", "Network"]
+        );
+        assert_eq!(path.name(), Some("Network"));
+        assert_eq!(path.to_string(), "/Sets/ABC/Network");
+        assert_eq!("/".parse(), Ok(PreferencesPath::root()));
+    }
+
+    #[test]
+    fn parse_errors() {
+        let kind = |path: &str| path.parse::<PreferencesPath>().unwrap_err().kind();
+        assert_eq!(kind(""), PathErrorKind::NotAbsolute);
+        assert_eq!(kind("Sets"), PathErrorKind::NotAbsolute);
+        assert_eq!(kind("/Sets//Network"), PathErrorKind::EmptySegment);
+        assert_eq!(kind("/Sets/"), PathErrorKind::EmptySegment);
+        assert_eq!(kind("//"), PathErrorKind::EmptySegment);
+    }
+
+    #[test]
+    fn join() {
+        let sets = PreferencesPath::root().join("Sets").unwrap();
+        assert_eq!(sets.as_str(), "/Sets");
+        assert_eq!(sets.join("ABC").unwrap().as_str(), "/Sets/ABC");
+
+        let error = sets.join("A/B").unwrap_err();
+        assert_eq!(error.kind(), PathErrorKind::SeparatorInSegment);
+        assert_eq!(error.path(), "/Sets/A/B");
+        assert_eq!(
+            sets.join("").unwrap_err().kind(),
+            PathErrorKind::EmptySegment
+        );
+    }
+
+    #[test]
+    fn parent() {
+        let path = "/Sets/ABC".parse::<PreferencesPath>().unwrap();
+        let parent = path.parent().unwrap();
+        assert_eq!(parent.as_str(), "/Sets");
+        assert_eq!(parent.parent(), Some(PreferencesPath::root()));
+        assert_eq!(PreferencesPath::root().parent(), None);
+        assert_eq!(PreferencesPath::root().segments().count(), 0);
+        assert_eq!(PreferencesPath::root().name(), None);
+    }
+
+    #[test]
+    fn values_and_links() {
+        let file = std::env::temp_dir().join(format!(
+            "system-configuration-rs-paths-{}.plist",
+            std::process::id()
+        ));
+        let preferences = SCPreferences::group(
+            &CFString::new("test"),
+            &CFString::new(file.to_str().unwrap()),
+        )
+        .unwrap();
+        let services = PreferencesPath::root().join("Services").unwrap();
+        let service = preferences.create_unique_child(&services).unwrap();
+        assert_eq!(service.parent(), Some(services));
+
+        let value = CFDictionary::from_CFType_pairs(&[(
+            CFString::new("Name"),
+            CFString::new("Test").into_CFType(),
+        )]);
+        preferences.set_path_value(&service, &value).unwrap();
+        let link = "/Current".parse::<PreferencesPath>().unwrap();
+        preferences.set_path_link(&link, &service).unwrap();
+
+        assert_eq!(preferences.get_path_link(&link), Some(service.clone()));
+        assert_eq!(preferences.get_path_link(&service), None);
+        let linked = preferences.get_path_value(&link).unwrap();
+        assert!(linked.contains_key(&CFString::new("Name")));
+
+        preferences.remove_path(&link).unwrap();
+        assert!(preferences.get_path_value(&link).is_none());
+        assert!(preferences.get_path_value(&service).is_some());
+    }
+}
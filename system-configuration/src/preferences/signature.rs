@@ -0,0 +1,156 @@
+//! Optimistic concurrency for preferences edited by several processes at once.
+
+use super::SCPreferences;
+use crate::{
+    error::SCError,
+    sys::preferences::{SCPreferencesGetSignature, SCPreferencesSynchronize},
+};
+use core_foundation::{base::TCFType, data::CFData};
+use std::{error::Error, fmt};
+
+/// Identifies the version of the stored preferences that a session was read from. Two
+/// signatures are equal if the underlying storage did not change in between.
+///
+/// Returned by [`SCPreferences::signature`].
+///
+/// [`SCPreferences::signature`]: struct.SCPreferences.html#method.signature
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PreferencesSignature(Vec<u8>);
+
+impl PreferencesSignature {
+    /// Returns the raw bytes of the signature.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+/// Failure to commit preferences with [`SCPreferences::commit_if_unchanged`].
+///
+/// [`SCPreferences::commit_if_unchanged`]: struct.SCPreferences.html#method.commit_if_unchanged
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommitError {
+    /// The stored preferences were changed by another writer since this session read them.
+    /// Nothing was written.
+    Conflict,
+    /// Locking or committing the preferences failed.
+    Preferences(SCError),
+}
+
+impl fmt::Display for CommitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Conflict => write!(f, "Preferences were changed by another writer"),
+            Self::Preferences(error) => write!(f, "Failed to commit preferences: {}", error),
+        }
+    }
+}
+
+impl Error for CommitError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Conflict => None,
+            Self::Preferences(error) => Some(error),
+        }
+    }
+}
+
+impl From<SCError> for CommitError {
+    fn from(error: SCError) -> Self {
+        CommitError::Preferences(error)
+    }
+}
+
+impl SCPreferences {
+    /// Returns the signature of the stored preferences this session was read from. Or `None` if
+    /// an error occurred.
+    ///
+    /// See [`SCPreferencesGetSignature`] for details.
+    ///
+    /// [`SCPreferencesGetSignature`]: https://developer.apple.com/documentation/systemconfiguration/1516838-scpreferencesgetsignature?language=objc
+    pub fn signature(&self) -> Option<PreferencesSignature> {
+        unsafe {
+            let data_ref = SCPreferencesGetSignature(self.as_concrete_TypeRef());
+            if data_ref.is_null() {
+                None
+            } else {
+                let data = CFData::wrap_under_get_rule(data_ref);
+                Some(PreferencesSignature(data.bytes().to_vec()))
+            }
+        }
+    }
+
+    /// Discards the cached preferences and any uncommitted changes, so the next access reads
+    /// them from storage again.
+    ///
+    /// See [`SCPreferencesSynchronize`] for details.
+    ///
+    /// [`SCPreferencesSynchronize`]: https://developer.apple.com/documentation/systemconfiguration/1516794-scpreferencessynchronize?language=objc
+    pub fn synchronize(&self) {
+        unsafe { SCPreferencesSynchronize(self.as_concrete_TypeRef()) }
+    }
+
+    /// Commits the changes staged in this session, but only if the stored preferences were not
+    /// changed by another writer since this session read them. Fails with
+    /// [`CommitError::Conflict`] without writing anything otherwise. Call [`synchronize`] to read
+    /// their changes before trying again.
+    ///
+    /// Conflicts are detected by [`lock`], which fails with [`SCError::Stale`] if the stored
+    /// preferences no longer match the ones this session read. The preferences are only locked
+    /// while checking for conflicts and committing, so they can be edited without holding the
+    /// lock. See [`lock`] for the meaning of `wait`.
+    ///
+    /// [`CommitError::Conflict`]: enum.CommitError.html#variant.Conflict
+    /// [`synchronize`]: #method.synchronize
+    /// [`lock`]: #method.lock
+    /// [`SCError::Stale`]: ../error/enum.SCError.html#variant.Stale
+    pub fn commit_if_unchanged(&self, wait: bool) -> Result<(), CommitError> {
+        let transaction = self.lock(wait).map_err(|error| match error {
+            SCError::Stale => CommitError::Conflict,
+            error => CommitError::Preferences(error),
+        })?;
+        transaction.commit()?;
+        transaction.unlock()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core_foundation::string::CFString;
+
+    #[test]
+    fn commit_if_unchanged_detects_conflict() {
+        let path = std::env::temp_dir().join(format!(
+            "system-configuration-rs-signature-{}.plist",
+            std::process::id()
+        ));
+        let prefs_id = CFString::new(path.to_str().unwrap());
+        let ours = SCPreferences::group(&CFString::new("ours"), &prefs_id).unwrap();
+        let theirs = SCPreferences::group(&CFString::new("theirs"), &prefs_id).unwrap();
+
+        ours.set("Writer", CFString::new("ours")).unwrap();
+        ours.commit_if_unchanged(false).unwrap();
+
+        let signature = ours.signature().unwrap();
+        theirs.set("Writer", CFString::new("theirs")).unwrap();
+        let transaction = theirs.lock(false).unwrap();
+        transaction.commit().unwrap();
+        transaction.unlock().unwrap();
+
+        ours.set("Writer", CFString::new("ours again")).unwrap();
+        assert_eq!(ours.commit_if_unchanged(false), Err(CommitError::Conflict));
+
+        ours.synchronize();
+        assert_ne!(ours.signature().as_ref(), Some(&signature));
+        assert_eq!(
+            ours.get("Writer")
+                .and_then(|value| value.downcast_into::<CFString>())
+                .map(|value| value.to_string()),
+            Some("theirs".to_owned())
+        );
+        ours.set("Writer", CFString::new("ours again")).unwrap();
+        ours.commit_if_unchanged(false).unwrap();
+        let _ = std::fs::remove_file(path);
+    }
+}
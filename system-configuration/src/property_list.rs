@@ -0,0 +1,211 @@
+//! A plain Rust representation of property list values.
+//!
+//! Values read from SystemConfiguration are Core Foundation property lists. Converting them to
+//! [`PropertyValue`] once makes it possible to inspect them, and to build typed models on top of
+//! them, without any calls into Core Foundation.
+//!
+//! [`PropertyValue`]: enum.PropertyValue.html
+
+use core_foundation::{
+    array::CFArray,
+    base::{CFType, TCFType},
+    boolean::CFBoolean,
+    data::CFData,
+    date::CFDate,
+    dictionary::CFDictionary,
+    number::CFNumber,
+    propertylist::CFPropertyList,
+    string::CFString,
+};
+use std::collections::BTreeMap;
+
+/// A property list dictionary with string keys.
+pub type PropertyDictionary = BTreeMap<String, PropertyValue>;
+
+/// A single property list value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PropertyValue {
+    /// A string.
+    String(String),
+    /// A number without a fractional part.
+    Integer(i64),
+    /// A floating point number.
+    Float(f64),
+    /// A boolean.
+    Boolean(bool),
+    /// Raw bytes.
+    Data(Vec<u8>),
+    /// A point in time, as seconds relative to 2001-01-01 00:00:00 UTC.
+    Date(f64),
+    /// An ordered list of values.
+    Array(Vec<PropertyValue>),
+    /// A dictionary. Entries with keys that are not strings are left out.
+    Dictionary(PropertyDictionary),
+}
+
+impl PropertyValue {
+    /// Converts a Core Foundation property list into a `PropertyValue`. Returns `None` if the
+    /// value, or any value nested in it, is not a valid property list type.
+    pub fn from_property_list(plist: &CFPropertyList) -> Option<Self> {
+        if let Some(string) = plist.downcast::<CFString>() {
+            Some(PropertyValue::String(string.to_string()))
+        } else if let Some(boolean) = plist.downcast::<CFBoolean>() {
+            Some(PropertyValue::Boolean(boolean.into()))
+        } else if let Some(number) = plist.downcast::<CFNumber>() {
+            // Converting a number with a fractional part to an integer is lossy, and fails.
+            match number.to_i64() {
+                Some(integer) => Some(PropertyValue::Integer(integer)),
+                None => number.to_f64().map(PropertyValue::Float),
+            }
+        } else if let Some(data) = plist.downcast::<CFData>() {
+            Some(PropertyValue::Data(data.bytes().to_vec()))
+        } else if let Some(date) = plist.downcast::<CFDate>() {
+            Some(PropertyValue::Date(date.abs_time()))
+        } else if let Some(array) = plist.downcast::<CFArray>() {
+            array
+                .iter()
+                .map(|item| unsafe {
+                    Self::from_property_list(&CFPropertyList::wrap_under_get_rule(*item))
+                })
+                .collect::<Option<Vec<_>>>()
+                .map(PropertyValue::Array)
+        } else if let Some(dictionary) = plist.downcast::<CFDictionary>() {
+            let (keys, values) = dictionary.get_keys_and_values();
+            let mut entries = PropertyDictionary::new();
+            for (key, value) in keys.into_iter().zip(values) {
+                let key = unsafe { CFType::wrap_under_get_rule(key) };
+                if let Some(key) = key.downcast::<CFString>() {
+                    let value = unsafe { CFPropertyList::wrap_under_get_rule(value) };
+                    entries.insert(key.to_string(), Self::from_property_list(&value)?);
+                }
+            }
+            Some(PropertyValue::Dictionary(entries))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the string, if this is a string.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            PropertyValue::String(string) => Some(string),
+            _ => None,
+        }
+    }
+
+    /// Returns the integer, if this is an integer.
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            PropertyValue::Integer(integer) => Some(integer),
+            _ => None,
+        }
+    }
+
+    /// Returns the boolean, if this is a boolean. Integers are also accepted, with zero as
+    /// `false`, since SystemConfiguration stores most flags as numbers.
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self {
+            PropertyValue::Boolean(boolean) => Some(boolean),
+            PropertyValue::Integer(integer) => Some(integer != 0),
+            _ => None,
+        }
+    }
+
+    /// Returns the array, if this is an array.
+    pub fn as_array(&self) -> Option<&[PropertyValue]> {
+        match self {
+            PropertyValue::Array(array) => Some(array),
+            _ => None,
+        }
+    }
+
+    /// Returns the dictionary, if this is a dictionary.
+    pub fn as_dictionary(&self) -> Option<&PropertyDictionary> {
+        match self {
+            PropertyValue::Dictionary(dictionary) => Some(dictionary),
+            _ => None,
+        }
+    }
+}
+
+impl From<&str> for PropertyValue {
+    fn from(string: &str) -> Self {
+        PropertyValue::String(string.to_owned())
+    }
+}
+
+impl From<String> for PropertyValue {
+    fn from(string: String) -> Self {
+        PropertyValue::String(string)
+    }
+}
+
+impl From<i64> for PropertyValue {
+    fn from(integer: i64) -> Self {
+        PropertyValue::Integer(integer)
+    }
+}
+
+impl From<bool> for PropertyValue {
+    fn from(boolean: bool) -> Self {
+        PropertyValue::Boolean(boolean)
+    }
+}
+
+impl From<Vec<PropertyValue>> for PropertyValue {
+    fn from(array: Vec<PropertyValue>) -> Self {
+        PropertyValue::Array(array)
+    }
+}
+
+impl From<PropertyDictionary> for PropertyValue {
+    fn from(dictionary: PropertyDictionary) -> Self {
+        PropertyValue::Dictionary(dictionary)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core_foundation::propertylist::CFPropertyListSubClass;
+
+    #[test]
+    fn from_property_list() {
+        let array = CFArray::from_CFTypes(&[
+            CFNumber::from(8080).into_CFType(),
+            CFNumber::from(0.5).into_CFType(),
+            CFBoolean::true_value().into_CFType(),
+        ]);
+        let dictionary = CFDictionary::from_CFType_pairs(&[
+            (
+                CFString::new("Host"),
+                CFString::new("proxy.example.com").into_CFType(),
+            ),
+            (CFString::new("Values"), array.into_CFType()),
+        ]);
+
+        let expected = PropertyDictionary::from([
+            ("Host".to_owned(), PropertyValue::from("proxy.example.com")),
+            (
+                "Values".to_owned(),
+                PropertyValue::Array(vec![
+                    PropertyValue::Integer(8080),
+                    PropertyValue::Float(0.5),
+                    PropertyValue::Boolean(true),
+                ]),
+            ),
+        ]);
+        assert_eq!(
+            PropertyValue::from_property_list(&dictionary.to_untyped().to_CFPropertyList()),
+            Some(PropertyValue::Dictionary(expected))
+        );
+    }
+
+    #[test]
+    fn flags_as_bool() {
+        assert_eq!(PropertyValue::Integer(1).as_bool(), Some(true));
+        assert_eq!(PropertyValue::Integer(0).as_bool(), Some(false));
+        assert_eq!(PropertyValue::Boolean(true).as_bool(), Some(true));
+        assert_eq!(PropertyValue::from("1").as_bool(), None);
+    }
+}
@@ -0,0 +1,466 @@
+//! Typed model of the system proxy settings.
+//!
+//! The settings are parsed from the dictionary returned by [`SCDynamicStore::get_proxies`], after
+//! converting it to a [`PropertyDictionary`]. The parsing itself does not call into Core
+//! Foundation, so it can be used and tested with hand built dictionaries on any platform.
+//!
+//! [`ProxySettings::resolve_url`] decides which proxy to use for a URL, the same way macOS does.
+//!
+//! See the `kSCPropNetProxies*` keys in [`SCSchemaDefinitions`] for the meaning of each setting.
+//!
+//! [`SCDynamicStore::get_proxies`]: ../dynamic_store/struct.SCDynamicStore.html#method.get_proxies
+//! [`ProxySettings::resolve_url`]: struct.ProxySettings.html#method.resolve_url
+//! [`PropertyDictionary`]: ../property_list/type.PropertyDictionary.html
+//! [`SCSchemaDefinitions`]: https://developer.apple.com/documentation/systemconfiguration/scschemadefinitions?language=objc
+
+mod resolver;
+
+pub use self::resolver::{InvalidUrlError, PacSource, ProxyKind, ProxyResolution, ProxyServer};
+
+use crate::{
+    dynamic_store::SCDynamicStore,
+    error::SCError,
+    property_list::{PropertyDictionary, PropertyValue},
+};
+use core_foundation::{base::TCFType, propertylist::CFPropertyList};
+use std::{collections::BTreeMap, convert::TryFrom, error::Error, fmt};
+
+/// The dictionary keys of the proxy settings. The values are the same as the `kSCPropNetProxies*`
+/// constants in `system_configuration_sys::schema_definitions`, but as string literals, so the
+/// settings can be parsed and compared without dereferencing framework statics. A test checks
+/// that they stay equal.
+pub mod keys {
+    /// `kSCPropNetProxiesExceptionsList`.
+    pub const EXCEPTIONS_LIST: &str = "ExceptionsList";
+    /// `kSCPropNetProxiesExcludeSimpleHostnames`.
+    pub const EXCLUDE_SIMPLE_HOSTNAMES: &str = "ExcludeSimpleHostnames";
+    /// `kSCPropNetProxiesFTPEnable`.
+    pub const FTP_ENABLE: &str = "FTPEnable";
+    /// `kSCPropNetProxiesFTPPassive`.
+    pub const FTP_PASSIVE: &str = "FTPPassive";
+    /// `kSCPropNetProxiesFTPPort`.
+    pub const FTP_PORT: &str = "FTPPort";
+    /// `kSCPropNetProxiesFTPProxy`.
+    pub const FTP_PROXY: &str = "FTPProxy";
+    /// `kSCPropNetProxiesGopherEnable`.
+    pub const GOPHER_ENABLE: &str = "GopherEnable";
+    /// `kSCPropNetProxiesGopherPort`.
+    pub const GOPHER_PORT: &str = "GopherPort";
+    /// `kSCPropNetProxiesGopherProxy`.
+    pub const GOPHER_PROXY: &str = "GopherProxy";
+    /// `kSCPropNetProxiesHTTPEnable`.
+    pub const HTTP_ENABLE: &str = "HTTPEnable";
+    /// `kSCPropNetProxiesHTTPPort`.
+    pub const HTTP_PORT: &str = "HTTPPort";
+    /// `kSCPropNetProxiesHTTPProxy`.
+    pub const HTTP_PROXY: &str = "HTTPProxy";
+    /// `kSCPropNetProxiesHTTPSEnable`.
+    pub const HTTPS_ENABLE: &str = "HTTPSEnable";
+    /// `kSCPropNetProxiesHTTPSPort`.
+    pub const HTTPS_PORT: &str = "HTTPSPort";
+    /// `kSCPropNetProxiesHTTPSProxy`.
+    pub const HTTPS_PROXY: &str = "HTTPSProxy";
+    /// `kSCPropNetProxiesRTSPEnable`.
+    pub const RTSP_ENABLE: &str = "RTSPEnable";
+    /// `kSCPropNetProxiesRTSPPort`.
+    pub const RTSP_PORT: &str = "RTSPPort";
+    /// `kSCPropNetProxiesRTSPProxy`.
+    pub const RTSP_PROXY: &str = "RTSPProxy";
+    /// `kSCPropNetProxiesSOCKSEnable`.
+    pub const SOCKS_ENABLE: &str = "SOCKSEnable";
+    /// `kSCPropNetProxiesSOCKSPort`.
+    pub const SOCKS_PORT: &str = "SOCKSPort";
+    /// `kSCPropNetProxiesSOCKSProxy`.
+    pub const SOCKS_PROXY: &str = "SOCKSProxy";
+    /// `kSCPropNetProxiesProxyAutoConfigEnable`.
+    pub const PROXY_AUTO_CONFIG_ENABLE: &str = "ProxyAutoConfigEnable";
+    /// `kSCPropNetProxiesProxyAutoConfigJavaScript`.
+    pub const PROXY_AUTO_CONFIG_JAVASCRIPT: &str = "ProxyAutoConfigJavaScript";
+    /// `kSCPropNetProxiesProxyAutoConfigURLString`.
+    pub const PROXY_AUTO_CONFIG_URL_STRING: &str = "ProxyAutoConfigURLString";
+    /// `kSCPropNetProxiesProxyAutoDiscoveryEnable`.
+    pub const PROXY_AUTO_DISCOVERY_ENABLE: &str = "ProxyAutoDiscoveryEnable";
+    /// The per-interface proxy settings, keyed by interface name. Only defined in the private
+    /// SystemConfiguration headers, so there is no public constant for it.
+    pub const SCOPED: &str = "__SCOPED__";
+}
+
+/// The proxy settings for a single protocol.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct ProtocolProxy {
+    /// Whether the proxy is enabled. The host and port may be set even if it is not.
+    pub enabled: bool,
+    /// The host name or address of the proxy server.
+    pub host: Option<String>,
+    /// The port of the proxy server.
+    pub port: Option<u16>,
+}
+
+impl ProtocolProxy {
+    /// Returns the host and port of the proxy, if it is enabled and has a non-empty host.
+    pub fn server(&self) -> Option<(&str, Option<u16>)> {
+        match self.host.as_deref() {
+            Some(host) if self.enabled && !host.is_empty() => Some((host, self.port)),
+            _ => None,
+        }
+    }
+
+    fn parse(settings: &PropertyDictionary, enable: &str, host: &str, port: &str) -> Self {
+        ProtocolProxy {
+            enabled: get_bool(settings, enable),
+            host: settings
+                .get(host)
+                .and_then(PropertyValue::as_str)
+                .map(str::to_owned),
+            port: settings
+                .get(port)
+                .and_then(PropertyValue::as_i64)
+                .and_then(|port| u16::try_from(port).ok()),
+        }
+    }
+}
+
+/// Proxy auto-configuration (PAC) settings.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct ProxyAutoConfig {
+    /// Whether proxy auto-configuration is enabled.
+    pub enabled: bool,
+    /// The URL of the PAC file.
+    pub url: Option<String>,
+    /// The PAC script itself, used instead of downloading it from `url`.
+    pub javascript: Option<String>,
+}
+
+/// The system proxy settings.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProxySettings {
+    /// The proxy for HTTP.
+    pub http: ProtocolProxy,
+    /// The proxy for HTTPS.
+    pub https: ProtocolProxy,
+    /// The proxy for FTP.
+    pub ftp: ProtocolProxy,
+    /// Whether FTP should use passive mode.
+    pub ftp_passive: bool,
+    /// The SOCKS proxy, used for all protocols.
+    pub socks: ProtocolProxy,
+    /// The proxy for RTSP.
+    pub rtsp: ProtocolProxy,
+    /// The proxy for Gopher.
+    pub gopher: ProtocolProxy,
+    /// Whether the proxy should be discovered automatically with WPAD.
+    pub auto_discovery_enabled: bool,
+    /// Proxy auto-configuration settings.
+    pub auto_config: ProxyAutoConfig,
+    /// Hosts and domains that should be reached directly, as glob patterns or CIDR ranges.
+    pub exceptions: Vec<String>,
+    /// Whether host names without a dot should be reached directly.
+    pub exclude_simple_hostnames: bool,
+    /// The proxy settings of individual network interfaces, keyed by interface name.
+    pub scoped: BTreeMap<String, ProxySettings>,
+}
+
+impl ProxySettings {
+    /// Parses proxy settings from a dictionary with `kSCPropNetProxies*` keys. Missing settings,
+    /// and settings with values of the wrong type, keep their default value.
+    pub fn from_dictionary(settings: &PropertyDictionary) -> Self {
+        let scoped = settings
+            .get(keys::SCOPED)
+            .and_then(PropertyValue::as_dictionary)
+            .map(|scoped| {
+                scoped
+                    .iter()
+                    .filter_map(|(interface, settings)| {
+                        let settings = Self::from_dictionary(settings.as_dictionary()?);
+                        Some((interface.clone(), settings))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        let exceptions = settings
+            .get(keys::EXCEPTIONS_LIST)
+            .and_then(PropertyValue::as_array)
+            .map(|exceptions| {
+                exceptions
+                    .iter()
+                    .filter_map(PropertyValue::as_str)
+                    .map(str::to_owned)
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        ProxySettings {
+            http: ProtocolProxy::parse(
+                settings,
+                keys::HTTP_ENABLE,
+                keys::HTTP_PROXY,
+                keys::HTTP_PORT,
+            ),
+            https: ProtocolProxy::parse(
+                settings,
+                keys::HTTPS_ENABLE,
+                keys::HTTPS_PROXY,
+                keys::HTTPS_PORT,
+            ),
+            ftp: ProtocolProxy::parse(settings, keys::FTP_ENABLE, keys::FTP_PROXY, keys::FTP_PORT),
+            ftp_passive: get_bool(settings, keys::FTP_PASSIVE),
+            socks: ProtocolProxy::parse(
+                settings,
+                keys::SOCKS_ENABLE,
+                keys::SOCKS_PROXY,
+                keys::SOCKS_PORT,
+            ),
+            rtsp: ProtocolProxy::parse(
+                settings,
+                keys::RTSP_ENABLE,
+                keys::RTSP_PROXY,
+                keys::RTSP_PORT,
+            ),
+            gopher: ProtocolProxy::parse(
+                settings,
+                keys::GOPHER_ENABLE,
+                keys::GOPHER_PROXY,
+                keys::GOPHER_PORT,
+            ),
+            auto_discovery_enabled: get_bool(settings, keys::PROXY_AUTO_DISCOVERY_ENABLE),
+            auto_config: ProxyAutoConfig {
+                enabled: get_bool(settings, keys::PROXY_AUTO_CONFIG_ENABLE),
+                url: get_string(settings, keys::PROXY_AUTO_CONFIG_URL_STRING),
+                javascript: get_string(settings, keys::PROXY_AUTO_CONFIG_JAVASCRIPT),
+            },
+            exceptions,
+            exclude_simple_hostnames: get_bool(settings, keys::EXCLUDE_SIMPLE_HOSTNAMES),
+            scoped,
+        }
+    }
+}
+
+/// Failure to read the proxy settings with [`SCDynamicStore::get_proxy_settings`].
+///
+/// [`SCDynamicStore::get_proxy_settings`]: ../dynamic_store/struct.SCDynamicStore.html#method.get_proxy_settings
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProxySettingsError {
+    /// Reading the proxy settings from the dynamic store failed.
+    Store(SCError),
+    /// The proxy settings contain values that are not property list values.
+    InvalidValue,
+}
+
+impl fmt::Display for ProxySettingsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Store(error) => write!(f, "Failed to read proxy settings: {}", error),
+            Self::InvalidValue => write!(f, "Proxy settings contain an invalid value"),
+        }
+    }
+}
+
+impl Error for ProxySettingsError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Store(error) => Some(error),
+            Self::InvalidValue => None,
+        }
+    }
+}
+
+impl From<SCError> for ProxySettingsError {
+    fn from(error: SCError) -> Self {
+        ProxySettingsError::Store(error)
+    }
+}
+
+impl SCDynamicStore {
+    /// Returns the current internet proxy settings as a [`ProxySettings`]. Fails with
+    /// [`ProxySettingsError::InvalidValue`] if the settings contain values that are not property
+    /// list values.
+    ///
+    /// [`ProxySettings`]: ../proxies/struct.ProxySettings.html
+    /// [`ProxySettingsError::InvalidValue`]: ../proxies/enum.ProxySettingsError.html#variant.InvalidValue
+    pub fn get_proxy_settings(&self) -> Result<ProxySettings, ProxySettingsError> {
+        let proxies = self.get_proxies()?;
+        let proxies = unsafe { CFPropertyList::wrap_under_get_rule(proxies.as_CFTypeRef()) };
+        match PropertyValue::from_property_list(&proxies) {
+            Some(PropertyValue::Dictionary(settings)) => {
+                Ok(ProxySettings::from_dictionary(&settings))
+            }
+            _ => Err(ProxySettingsError::InvalidValue),
+        }
+    }
+}
+
+fn get_bool(settings: &PropertyDictionary, key: &str) -> bool {
+    settings
+        .get(key)
+        .and_then(PropertyValue::as_bool)
+        .unwrap_or(false)
+}
+
+fn get_string(settings: &PropertyDictionary, key: &str) -> Option<String> {
+    settings
+        .get(key)
+        .and_then(PropertyValue::as_str)
+        .map(str::to_owned)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dictionary<const N: usize>(entries: [(&str, PropertyValue); N]) -> PropertyDictionary {
+        entries
+            .into_iter()
+            .map(|(key, value)| (key.to_owned(), value))
+            .collect()
+    }
+
+    #[test]
+    fn empty_settings() {
+        let settings = ProxySettings::from_dictionary(&PropertyDictionary::new());
+        assert_eq!(settings, ProxySettings::default());
+        assert_eq!(settings.http.server(), None);
+    }
+
+    #[test]
+    fn protocol_proxies() {
+        let settings = ProxySettings::from_dictionary(&dictionary([
+            (keys::HTTP_ENABLE, 1.into()),
+            (keys::HTTP_PROXY, "proxy.example.com".into()),
+            (keys::HTTP_PORT, 3128.into()),
+            (keys::HTTPS_ENABLE, 0.into()),
+            (keys::HTTPS_PROXY, "secure.example.com".into()),
+            (keys::HTTPS_PORT, 443.into()),
+            (keys::SOCKS_ENABLE, true.into()),
+            (keys::SOCKS_PROXY, "".into()),
+            (keys::FTP_ENABLE, 1.into()),
+            (keys::FTP_PROXY, "ftp.example.com".into()),
+            (keys::FTP_PORT, 70000.into()),
+            (keys::FTP_PASSIVE, 1.into()),
+        ]));
+
+        assert_eq!(
+            settings.http,
+            ProtocolProxy {
+                enabled: true,
+                host: Some("proxy.example.com".to_owned()),
+                port: Some(3128),
+            }
+        );
+        assert_eq!(
+            settings.http.server(),
+            Some(("proxy.example.com", Some(3128)))
+        );
+        assert!(!settings.https.enabled);
+        assert_eq!(settings.https.server(), None);
+        assert_eq!(settings.socks.server(), None);
+        assert_eq!(settings.ftp.port, None);
+        assert!(settings.ftp_passive);
+        assert_eq!(settings.rtsp, ProtocolProxy::default());
+    }
+
+    #[test]
+    fn auto_config_and_exceptions() {
+        let settings = ProxySettings::from_dictionary(&dictionary([
+            (keys::PROXY_AUTO_DISCOVERY_ENABLE, 1.into()),
+            (keys::PROXY_AUTO_CONFIG_ENABLE, 1.into()),
+            (
+                keys::PROXY_AUTO_CONFIG_URL_STRING,
+                "http://wpad.example.com/proxy.pac".into(),
+            ),
+            (
+                keys::EXCEPTIONS_LIST,
+                vec!["*.local".into(), 42.into(), "169.254/16".into()].into(),
+            ),
+            (keys::EXCLUDE_SIMPLE_HOSTNAMES, 1.into()),
+        ]));
+
+        assert!(settings.auto_discovery_enabled);
+        assert_eq!(
+            settings.auto_config,
+            ProxyAutoConfig {
+                enabled: true,
+                url: Some("http://wpad.example.com/proxy.pac".to_owned()),
+                javascript: None,
+            }
+        );
+        assert_eq!(settings.exceptions, ["*.local", "169.254/16"]);
+        assert!(settings.exclude_simple_hostnames);
+    }
+
+    #[test]
+    fn scoped_settings() {
+        let en0 = dictionary([
+            (keys::HTTP_ENABLE, 1.into()),
+            (keys::HTTP_PROXY, "en0.example.com".into()),
+        ]);
+        let settings = ProxySettings::from_dictionary(&dictionary([
+            (keys::HTTP_ENABLE, 0.into()),
+            (
+                keys::SCOPED,
+                dictionary([("en0", en0.into()), ("utun0", "invalid".into())]).into(),
+            ),
+        ]));
+
+        assert_eq!(settings.scoped.len(), 1);
+        assert_eq!(
+            settings.scoped["en0"].http.server(),
+            Some(("en0.example.com", None))
+        );
+        assert_eq!(settings.http.server(), None);
+    }
+
+    #[test]
+    fn keys_match_schema_definitions() {
+        use crate::sys::schema_definitions::*;
+        use core_foundation::string::CFString;
+
+        let schema_keys = unsafe {
+            [
+                (keys::EXCEPTIONS_LIST, kSCPropNetProxiesExceptionsList),
+                (
+                    keys::EXCLUDE_SIMPLE_HOSTNAMES,
+                    kSCPropNetProxiesExcludeSimpleHostnames,
+                ),
+                (keys::FTP_ENABLE, kSCPropNetProxiesFTPEnable),
+                (keys::FTP_PASSIVE, kSCPropNetProxiesFTPPassive),
+                (keys::FTP_PORT, kSCPropNetProxiesFTPPort),
+                (keys::FTP_PROXY, kSCPropNetProxiesFTPProxy),
+                (keys::GOPHER_ENABLE, kSCPropNetProxiesGopherEnable),
+                (keys::GOPHER_PORT, kSCPropNetProxiesGopherPort),
+                (keys::GOPHER_PROXY, kSCPropNetProxiesGopherProxy),
+                (keys::HTTP_ENABLE, kSCPropNetProxiesHTTPEnable),
+                (keys::HTTP_PORT, kSCPropNetProxiesHTTPPort),
+                (keys::HTTP_PROXY, kSCPropNetProxiesHTTPProxy),
+                (keys::HTTPS_ENABLE, kSCPropNetProxiesHTTPSEnable),
+                (keys::HTTPS_PORT, kSCPropNetProxiesHTTPSPort),
+                (keys::HTTPS_PROXY, kSCPropNetProxiesHTTPSProxy),
+                (keys::RTSP_ENABLE, kSCPropNetProxiesRTSPEnable),
+                (keys::RTSP_PORT, kSCPropNetProxiesRTSPPort),
+                (keys::RTSP_PROXY, kSCPropNetProxiesRTSPProxy),
+                (keys::SOCKS_ENABLE, kSCPropNetProxiesSOCKSEnable),
+                (keys::SOCKS_PORT, kSCPropNetProxiesSOCKSPort),
+                (keys::SOCKS_PROXY, kSCPropNetProxiesSOCKSProxy),
+                (
+                    keys::PROXY_AUTO_CONFIG_ENABLE,
+                    kSCPropNetProxiesProxyAutoConfigEnable,
+                ),
+                (
+                    keys::PROXY_AUTO_CONFIG_JAVASCRIPT,
+                    kSCPropNetProxiesProxyAutoConfigJavaScript,
+                ),
+                (
+                    keys::PROXY_AUTO_CONFIG_URL_STRING,
+                    kSCPropNetProxiesProxyAutoConfigURLString,
+                ),
+                (
+                    keys::PROXY_AUTO_DISCOVERY_ENABLE,
+                    kSCPropNetProxiesProxyAutoDiscoveryEnable,
+                ),
+            ]
+        };
+        for (key, schema_key) in schema_keys.iter() {
+            let schema_key = unsafe { CFString::wrap_under_get_rule(*schema_key) };
+            assert_eq!(*key, schema_key.to_string());
+        }
+    }
+}
@@ -0,0 +1,479 @@
+//! Decides which proxy, if any, to use for a given URL or host.
+//!
+//! The rules follow how macOS applies the system proxy settings:
+//!
+//! 1. Hosts matching the exceptions list, and simple host names if `exclude_simple_hostnames` is
+//!    set, are reached directly.
+//! 2. If proxy auto-configuration or auto-discovery is enabled, the PAC script decides.
+//! 3. The enabled proxy for the scheme of the URL is used, falling back to the SOCKS proxy.
+//! 4. Otherwise the host is reached directly.
+//!
+//! Entries in the exceptions list are either
+//! * IP networks in CIDR notation, where trailing zero octets of IPv4 networks may be left out,
+//!   such as `169.254/16`,
+//! * IP addresses, matching only that exact address,
+//! * host names, where `*` matches any sequence of characters, such as `*.local`. Host names
+//!   without a wildcard also match all their subdomains.
+//!
+//! Matching is case insensitive and does not resolve host names.
+
+use super::{ProtocolProxy, ProxySettings};
+use std::{
+    fmt,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+};
+
+/// The kind of a proxy server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ProxyKind {
+    /// An HTTP proxy.
+    Http,
+    /// An HTTPS proxy.
+    Https,
+    /// An FTP proxy.
+    Ftp,
+    /// A SOCKS proxy.
+    Socks,
+    /// An RTSP proxy.
+    Rtsp,
+    /// A Gopher proxy.
+    Gopher,
+}
+
+/// A proxy server to connect through.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ProxyServer {
+    /// The kind of proxy.
+    pub kind: ProxyKind,
+    /// The host name or address of the proxy server.
+    pub host: String,
+    /// The port of the proxy server, if one is configured.
+    pub port: Option<u16>,
+}
+
+/// Where to get the proxy auto-configuration (PAC) script from.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PacSource {
+    /// Download the script from this URL.
+    Url(String),
+    /// The script itself.
+    JavaScript(String),
+    /// Discover the script with WPAD.
+    AutoDiscovery,
+}
+
+/// How to reach a URL or host.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ProxyResolution {
+    /// Connect directly, without a proxy.
+    Direct,
+    /// Connect through the given proxy.
+    Proxy(ProxyServer),
+    /// Run the proxy auto-configuration script to find the proxy.
+    UsePac(PacSource),
+}
+
+/// Error returned by [`ProxySettings::resolve_url`] when the URL can not be parsed.
+///
+/// [`ProxySettings::resolve_url`]: struct.ProxySettings.html#method.resolve_url
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidUrlError(String);
+
+impl fmt::Display for InvalidUrlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid URL: {}", self.0)
+    }
+}
+
+impl std::error::Error for InvalidUrlError {}
+
+impl ProxySettings {
+    /// Returns how to reach `url`, which must have a scheme and a host, such as
+    /// `https://example.com:8443/path`.
+    pub fn resolve_url(&self, url: &str) -> Result<ProxyResolution, InvalidUrlError> {
+        let (scheme, host) = split_url(url).ok_or_else(|| InvalidUrlError(url.to_owned()))?;
+        Ok(self.resolve_host(scheme, host))
+    }
+
+    /// Returns how to reach `host` with the given URL `scheme`, such as `http`. The host may be a
+    /// host name or an IP address, with or without brackets around IPv6 addresses.
+    pub fn resolve_host(&self, scheme: &str, host: &str) -> ProxyResolution {
+        let host = normalize_host(host);
+        if self.is_excluded(&host) {
+            return ProxyResolution::Direct;
+        }
+        if let Some(source) = self.pac_source() {
+            return ProxyResolution::UsePac(source);
+        }
+
+        let scheme_proxy = match scheme.to_ascii_lowercase().as_str() {
+            "http" | "ws" => Some((ProxyKind::Http, &self.http)),
+            "https" | "wss" => Some((ProxyKind::Https, &self.https)),
+            "ftp" => Some((ProxyKind::Ftp, &self.ftp)),
+            "rtsp" => Some((ProxyKind::Rtsp, &self.rtsp)),
+            "gopher" => Some((ProxyKind::Gopher, &self.gopher)),
+            _ => None,
+        };
+        scheme_proxy
+            .and_then(|(kind, proxy)| proxy_server(kind, proxy))
+            .or_else(|| proxy_server(ProxyKind::Socks, &self.socks))
+            .map_or(ProxyResolution::Direct, ProxyResolution::Proxy)
+    }
+
+    fn is_excluded(&self, host: &str) -> bool {
+        let ip = parse_ip(host);
+        if self.exclude_simple_hostnames && ip.is_none() && !host.contains('.') {
+            return true;
+        }
+        self.exceptions
+            .iter()
+            .any(|exception| matches_exception(exception, host, ip))
+    }
+
+    fn pac_source(&self) -> Option<PacSource> {
+        let auto_config = &self.auto_config;
+        if auto_config.enabled {
+            if let Some(javascript) = non_empty(&auto_config.javascript) {
+                return Some(PacSource::JavaScript(javascript.to_owned()));
+            }
+            if let Some(url) = non_empty(&auto_config.url) {
+                return Some(PacSource::Url(url.to_owned()));
+            }
+        }
+        if self.auto_discovery_enabled {
+            return Some(PacSource::AutoDiscovery);
+        }
+        None
+    }
+}
+
+fn proxy_server(kind: ProxyKind, proxy: &ProtocolProxy) -> Option<ProxyServer> {
+    proxy.server().map(|(host, port)| ProxyServer {
+        kind,
+        host: host.to_owned(),
+        port,
+    })
+}
+
+fn non_empty(value: &Option<String>) -> Option<&str> {
+    value.as_deref().filter(|value| !value.is_empty())
+}
+
+/// Splits a URL into its scheme and host. Returns `None` if either is missing.
+fn split_url(url: &str) -> Option<(&str, &str)> {
+    let (scheme, rest) = url.split_once("://")?;
+    let valid_scheme = scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+        && scheme
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+    if !valid_scheme {
+        return None;
+    }
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or("");
+    let host_port = authority.rsplit('@').next().unwrap_or("");
+    let host = if let Some(bracketed) = host_port.strip_prefix('[') {
+        bracketed.split(']').next().unwrap_or("")
+    } else {
+        host_port.split(':').next().unwrap_or("")
+    };
+    if host.is_empty() {
+        None
+    } else {
+        Some((scheme, host))
+    }
+}
+
+fn normalize_host(host: &str) -> String {
+    let host = host.trim();
+    let host = host
+        .strip_prefix('[')
+        .and_then(|host| host.strip_suffix(']'))
+        .unwrap_or(host);
+    host.trim_end_matches('.').to_ascii_lowercase()
+}
+
+fn parse_ip(host: &str) -> Option<IpAddr> {
+    host.parse().ok()
+}
+
+fn matches_exception(exception: &str, host: &str, ip: Option<IpAddr>) -> bool {
+    let exception = normalize_host(exception);
+    if exception.is_empty() {
+        return false;
+    }
+    if let Some((network, prefix)) = exception.split_once('/') {
+        return match (ip, parse_network(network, prefix)) {
+            (Some(ip), Some((network, prefix))) => in_network(ip, network, prefix),
+            _ => false,
+        };
+    }
+    if let Some(exception_ip) = parse_ip(&exception) {
+        return ip == Some(exception_ip);
+    }
+    if exception.contains('*') {
+        glob_match(exception.as_bytes(), host.as_bytes())
+    } else {
+        let exception = exception.trim_start_matches('.');
+        host == exception
+            || host
+                .strip_suffix(exception)
+                .map_or(false, |prefix| prefix.ends_with('.'))
+    }
+}
+
+/// Parses the network part of a CIDR entry, allowing trailing zero octets of IPv4 networks to be
+/// left out.
+fn parse_network(network: &str, prefix: &str) -> Option<(IpAddr, u8)> {
+    let prefix = prefix.parse::<u8>().ok()?;
+    let network = if network.contains(':') {
+        IpAddr::V6(network.parse::<Ipv6Addr>().ok()?)
+    } else {
+        let mut octets = [0u8; 4];
+        let mut parts = 0;
+        for (octet, part) in octets.iter_mut().zip(network.split('.')) {
+            *octet = part.parse().ok()?;
+            parts += 1;
+        }
+        if parts == 0 || parts != network.split('.').count() {
+            return None;
+        }
+        IpAddr::V4(Ipv4Addr::from(octets))
+    };
+    let max_prefix = if network.is_ipv4() { 32 } else { 128 };
+    if prefix > max_prefix {
+        return None;
+    }
+    Some((network, prefix))
+}
+
+fn in_network(ip: IpAddr, network: IpAddr, prefix: u8) -> bool {
+    match (ip, network) {
+        (IpAddr::V4(ip), IpAddr::V4(network)) => {
+            let mask = u32::MAX.checked_shl(32 - u32::from(prefix)).unwrap_or(0);
+            u32::from(ip) & mask == u32::from(network) & mask
+        }
+        (IpAddr::V6(ip), IpAddr::V6(network)) => {
+            let mask = u128::MAX.checked_shl(128 - u32::from(prefix)).unwrap_or(0);
+            u128::from(ip) & mask == u128::from(network) & mask
+        }
+        _ => false,
+    }
+}
+
+/// Matches `text` against `pattern`, where `*` matches any sequence of bytes.
+fn glob_match(pattern: &[u8], text: &[u8]) -> bool {
+    let (mut p, mut t) = (0, 0);
+    // The position after the last `*` in the pattern, and the text position it was tried at.
+    let mut backtrack = None;
+    while t < text.len() {
+        if p < pattern.len() && pattern[p] == b'*' {
+            p += 1;
+            backtrack = Some((p, t));
+        } else if p < pattern.len() && pattern[p] == text[t] {
+            p += 1;
+            t += 1;
+        } else if let Some((star_p, star_t)) = backtrack {
+            p = star_p;
+            t = star_t + 1;
+            backtrack = Some((star_p, t));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == b'*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proxies::ProxyAutoConfig;
+
+    fn proxy(host: &str, port: u16) -> ProtocolProxy {
+        ProtocolProxy {
+            enabled: true,
+            host: Some(host.to_owned()),
+            port: Some(port),
+        }
+    }
+
+    fn server(kind: ProxyKind, host: &str, port: u16) -> ProxyResolution {
+        ProxyResolution::Proxy(ProxyServer {
+            kind,
+            host: host.to_owned(),
+            port: Some(port),
+        })
+    }
+
+    fn manual_settings() -> ProxySettings {
+        ProxySettings {
+            http: proxy("http.proxy", 3128),
+            https: proxy("https.proxy", 3129),
+            ftp: ProtocolProxy {
+                enabled: false,
+                ..proxy("ftp.proxy", 2121)
+            },
+            exceptions: [
+                "*.local",
+                "169.254/16",
+                "10.0.0.0/8",
+                "fe80::/10",
+                "192.168.1.1",
+                "internal.example.com",
+                "*.corp.*",
+                ".intranet",
+            ]
+            .iter()
+            .map(|exception| exception.to_string())
+            .collect(),
+            exclude_simple_hostnames: true,
+            ..ProxySettings::default()
+        }
+    }
+
+    #[test]
+    fn corpus() {
+        use ProxyKind::*;
+        let settings = manual_settings();
+        let direct = ProxyResolution::Direct;
+        let corpus = [
+            ("http://example.com/", server(Http, "http.proxy", 3128)),
+            (
+                "https://example.com:8443/a?b#c",
+                server(Https, "https.proxy", 3129),
+            ),
+            (
+                "HTTP://User:pw@Example.COM/",
+                server(Http, "http.proxy", 3128),
+            ),
+            (
+                "wss://example.com/socket",
+                server(Https, "https.proxy", 3129),
+            ),
+            // FTP proxy is configured but disabled, and there is no SOCKS proxy.
+            ("ftp://files.example.com/", direct.clone()),
+            ("ssh://example.com/", direct.clone()),
+            // Glob exceptions.
+            ("http://printer.local/", direct.clone()),
+            ("http://printer.local./", direct.clone()),
+            ("http://local/", direct.clone()),
+            ("http://host.corp.example.com/", direct.clone()),
+            ("http://corp.example.com/", server(Http, "http.proxy", 3128)),
+            // Domain exceptions also match subdomains, but not other suffixes.
+            ("http://internal.example.com/", direct.clone()),
+            ("http://a.b.internal.example.com/", direct.clone()),
+            (
+                "http://notinternal.example.com/",
+                server(Http, "http.proxy", 3128),
+            ),
+            ("http://wiki.intranet/", direct.clone()),
+            ("http://intranet/", direct.clone()),
+            // CIDR and address exceptions.
+            ("http://169.254.10.20/", direct.clone()),
+            ("http://169.255.0.1/", server(Http, "http.proxy", 3128)),
+            ("http://10.1.2.3:8080/", direct.clone()),
+            ("http://192.168.1.1/", direct.clone()),
+            ("http://192.168.1.2/", server(Http, "http.proxy", 3128)),
+            ("http://[fe80::1]/", direct.clone()),
+            ("http://[2001:db8::1]:80/", server(Http, "http.proxy", 3128)),
+            // Simple host names.
+            ("http://intranet-wiki/", direct.clone()),
+            ("http://8.8.8.8/", server(Http, "http.proxy", 3128)),
+        ];
+        for (url, expected) in corpus.iter() {
+            assert_eq!(settings.resolve_url(url).as_ref(), Ok(expected), "{}", url);
+        }
+    }
+
+    #[test]
+    fn socks_fallback() {
+        let settings = ProxySettings {
+            http: proxy("http.proxy", 3128),
+            socks: proxy("socks.proxy", 1080),
+            ..ProxySettings::default()
+        };
+        assert_eq!(
+            settings.resolve_host("http", "example.com"),
+            server(ProxyKind::Http, "http.proxy", 3128)
+        );
+        assert_eq!(
+            settings.resolve_host("https", "example.com"),
+            server(ProxyKind::Socks, "socks.proxy", 1080)
+        );
+        assert_eq!(
+            settings.resolve_host("imap", "[::1]"),
+            server(ProxyKind::Socks, "socks.proxy", 1080)
+        );
+    }
+
+    #[test]
+    fn pac() {
+        let mut settings = ProxySettings {
+            http: proxy("http.proxy", 3128),
+            auto_discovery_enabled: true,
+            exceptions: vec!["*.local".to_owned()],
+            ..ProxySettings::default()
+        };
+        assert_eq!(
+            settings.resolve_host("http", "example.com"),
+            ProxyResolution::UsePac(PacSource::AutoDiscovery)
+        );
+        assert_eq!(
+            settings.resolve_host("http", "printer.local"),
+            ProxyResolution::Direct
+        );
+
+        settings.auto_config = ProxyAutoConfig {
+            enabled: true,
+            url: Some("http://wpad/proxy.pac".to_owned()),
+            javascript: None,
+        };
+        assert_eq!(
+            settings.resolve_host("http", "example.com"),
+            ProxyResolution::UsePac(PacSource::Url("http://wpad/proxy.pac".to_owned()))
+        );
+
+        settings.auto_config.enabled = false;
+        settings.auto_discovery_enabled = false;
+        assert_eq!(
+            settings.resolve_host("http", "example.com"),
+            server(ProxyKind::Http, "http.proxy", 3128)
+        );
+    }
+
+    #[test]
+    fn invalid_urls() {
+        let settings = ProxySettings::default();
+        for url in [
+            "example.com",
+            "http://",
+            "http:///path",
+            "1http://host/",
+            "://host",
+        ] {
+            assert_eq!(
+                settings.resolve_url(url),
+                Err(InvalidUrlError(url.to_owned()))
+            );
+        }
+    }
+
+    #[test]
+    fn invalid_exceptions_never_match() {
+        for exception in ["", "10/33", "10.x/8", "1.2.3.4.5/8", "fe80::/129"] {
+            assert!(!matches_exception(
+                exception,
+                "10.0.0.1",
+                parse_ip("10.0.0.1")
+            ));
+        }
+    }
+
+    #[test]
+    fn glob() {
+        assert!(glob_match(b"*", b""));
+        assert!(glob_match(b"a*b*c", b"aXbYbc"));
+        assert!(glob_match(b"*.example.com", b"www.example.com"));
+        assert!(!glob_match(b"*.example.com", b"example.com"));
+        assert!(!glob_match(b"a*b", b"aXbY"));
+    }
+}